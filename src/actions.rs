@@ -0,0 +1,204 @@
+use crate::runs::WorkflowRun;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Files provided by the GitHub Actions runner when `gha` runs as a workflow step
+#[derive(Debug, Default)]
+pub struct ActionsEnv {
+    /// `$GITHUB_STEP_SUMMARY`: markdown appended to the job summary
+    pub step_summary: Option<PathBuf>,
+    /// `$GITHUB_OUTPUT`: `name=value` step outputs
+    pub output: Option<PathBuf>,
+    /// Workflow token passed to the step as `$GITHUB_TOKEN` or, as for the `gh` CLI, `$GH_TOKEN`
+    pub token: Option<String>,
+}
+
+/// Hint for a 403 on dispatch inside GitHub Actions, where the workflow token often lacks the permission
+pub const DISPATCH_PERMISSION_HINT: &str =
+    "The workflow token may lack the permission to dispatch workflows; grant it with `permissions: actions: write` in the calling workflow";
+
+impl ActionsEnv {
+    /// Detect the runner from the process environment; `None` outside of GitHub Actions
+    pub fn detect() -> Option<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Detect the runner from variables looked up with `var`
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        if var("GITHUB_ACTIONS").as_deref() != Some("true") {
+            return None;
+        }
+        Some(Self {
+            step_summary: var("GITHUB_STEP_SUMMARY").filter(|v| !v.is_empty()).map(PathBuf::from),
+            output: var("GITHUB_OUTPUT").filter(|v| !v.is_empty()).map(PathBuf::from),
+            token: ["GITHUB_TOKEN", "GH_TOKEN"].into_iter().find_map(|name| var(name).filter(|v| !v.is_empty())),
+        })
+    }
+
+    /// Annotate a successful dispatch: a `::notice::` on stderr, which keeps stdout for the command output,
+    /// and a line in the step summary.
+    pub fn report_dispatch(&self, workflow: &str, git_ref: &str, run: Option<&WorkflowRun>) -> std::io::Result<()> {
+        let (notice, summary) = match run {
+            Some(run) => (
+                format!("Dispatched {workflow} on {git_ref}: {}", run.html_url),
+                format!("- Dispatched `{workflow}` on `{git_ref}`: [run {}]({})", run.id, run.html_url),
+            ),
+            None => (
+                format!("Dispatched {workflow} on {git_ref}"),
+                format!("- Dispatched `{workflow}` on `{git_ref}`"),
+            ),
+        };
+        eprintln!("::notice::{}", escape_data(&notice));
+        if let Some(path) = &self.step_summary {
+            append_line(path, &summary)?;
+        }
+        Ok(())
+    }
+
+    /// Expose the resolved runs of the `(workflow, ref, run)` dispatches as step outputs:
+    /// `run_id` and `run_url` for a single dispatch, a `runs` JSON list for several refs or workflows.
+    pub fn report_runs(&self, runs: &[(&str, &str, Option<&WorkflowRun>)]) -> std::io::Result<()> {
+        match runs {
+            [] | [(_, _, None)] => Ok(()),
+            [(_, _, Some(run))] => {
+                self.set_output("run_id", &run.id.to_string())?;
+                self.set_output("run_url", &run.html_url)
+            }
+            _ => {
+                let runs: Vec<_> = runs
+                    .iter()
+                    .map(|(workflow, git_ref, run)| {
+                        serde_json::json!({
+                            "workflow": workflow,
+                            "ref": git_ref,
+                            "run_id": run.map(|run| run.id),
+                            "run_url": run.map(|run| &run.html_url),
+                        })
+                    })
+                    .collect();
+                self.set_output("runs", &serde_json::to_string_pretty(&runs)?)
+            }
+        }
+    }
+
+    /// Append a step output; multi-line values use the delimiter form
+    pub fn set_output(&self, name: &str, value: &str) -> std::io::Result<()> {
+        let Some(path) = &self.output else {
+            return Ok(());
+        };
+        if value.contains('\n') {
+            let delimiter = format!("ghadelimiter_{}", std::process::id());
+            append_line(path, &format!("{name}<<{delimiter}\n{value}\n{delimiter}"))
+        } else {
+            append_line(path, &format!("{name}={value}"))
+        }
+    }
+}
+
+fn append_line(path: &PathBuf, line: &str) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{line}")
+}
+
+/// Escape a workflow command message as the runner expects
+fn escape_data(message: &str) -> String {
+    message.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run() -> WorkflowRun {
+        WorkflowRun {
+            id: 7,
+            status: Some("queued".into()),
+            conclusion: None,
+            event: "workflow_dispatch".into(),
+            head_branch: Some("main".into()),
+            html_url: "https://github.com/owner/repo/actions/runs/7".into(),
+        }
+    }
+
+    #[test]
+    fn detects_only_inside_actions() {
+        assert!(ActionsEnv::from_vars(|_| None).is_none());
+        assert!(ActionsEnv::from_vars(|name| (name == "GITHUB_ACTIONS").then(|| "false".to_string())).is_none());
+    }
+
+    #[test]
+    fn takes_the_workflow_token_from_github_token_or_gh_token() {
+        let env = |vars: &[(&str, &str)]| {
+            let vars: Vec<(String, String)> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+            ActionsEnv::from_vars(|name| vars.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone()))
+        };
+        assert_eq!(env(&[("GITHUB_ACTIONS", "true"), ("GH_TOKEN", "gh-token")]).unwrap().token.as_deref(), Some("gh-token"));
+        assert_eq!(
+            env(&[("GITHUB_ACTIONS", "true"), ("GITHUB_TOKEN", "workflow-token"), ("GH_TOKEN", "gh-token")]).unwrap().token.as_deref(),
+            Some("workflow-token")
+        );
+        assert_eq!(env(&[("GITHUB_ACTIONS", "true"), ("GITHUB_TOKEN", "")]).unwrap().token, None);
+        assert!(env(&[("GH_TOKEN", "gh-token")]).is_none());
+    }
+
+    #[test]
+    fn writes_run_outputs() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let output = temp.path().join("output");
+        let env = ActionsEnv { output: Some(output.clone()), ..Default::default() };
+
+        env.report_runs(&[("ci.yml", "main", Some(&run()))]).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&output).unwrap(),
+            "run_id=7\nrun_url=https://github.com/owner/repo/actions/runs/7\n"
+        );
+    }
+
+    #[test]
+    fn writes_a_run_list_for_several_dispatches() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let output = temp.path().join("output");
+        let env = ActionsEnv { output: Some(output.clone()), ..Default::default() };
+
+        env.report_runs(&[("ci.yml", "main", Some(&run())), ("ci.yml", "v1.0", None)]).unwrap();
+
+        let written = std::fs::read_to_string(&output).unwrap();
+        let delimiter = format!("ghadelimiter_{}", std::process::id());
+        let list = written
+            .strip_prefix(&format!("runs<<{delimiter}\n"))
+            .and_then(|rest| rest.strip_suffix(&format!("\n{delimiter}\n")))
+            .unwrap_or_else(|| panic!("not a multiline runs output: {written:?}"));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(list).unwrap(),
+            serde_json::json!([
+                {"workflow": "ci.yml", "ref": "main", "run_id": 7, "run_url": "https://github.com/owner/repo/actions/runs/7"},
+                {"workflow": "ci.yml", "ref": "v1.0", "run_id": null, "run_url": null},
+            ])
+        );
+    }
+
+    #[test]
+    fn appends_summary_lines() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let summary = temp.path().join("summary.md");
+        std::fs::write(&summary, "# Earlier step\n").unwrap();
+        let env = ActionsEnv::from_vars(|name| match name {
+            "GITHUB_ACTIONS" => Some("true".into()),
+            "GITHUB_STEP_SUMMARY" => Some(summary.display().to_string()),
+            _ => None,
+        })
+        .unwrap();
+
+        env.report_dispatch("ci.yml", "main", Some(&run())).unwrap();
+        env.report_dispatch("ci.yml", "v1.0", None).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&summary).unwrap(),
+            "# Earlier step\n\
+             - Dispatched `ci.yml` on `main`: [run 7](https://github.com/owner/repo/actions/runs/7)\n\
+             - Dispatched `ci.yml` on `v1.0`\n"
+        );
+    }
+}
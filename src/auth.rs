@@ -0,0 +1,112 @@
+use crate::github_api::{ApiError, GithubClient};
+use crate::output::OutputFormat;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+/// Authenticated user and the scopes reported for the token
+#[derive(Debug, Serialize)]
+pub struct AuthStatus {
+    pub login: String,
+    /// `X-OAuth-Scopes`; absent for fine-grained and GitHub App tokens
+    pub scopes: Option<String>,
+    /// `X-Accepted-OAuth-Scopes` of the `/user` endpoint
+    pub accepted_scopes: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct User {
+    login: String,
+}
+
+/// GET `/user` with the client's token
+pub async fn auth_status(gh: &GithubClient) -> anyhow::Result<AuthStatus> {
+    let url = format!("{}/user", gh.base_url());
+    let res = gh.execute(gh.request(Method::GET, &url)).await?;
+    let status = res.status();
+    if status == StatusCode::UNAUTHORIZED {
+        return Err(ApiError { status, message: "invalid or expired token".to_string() }.into());
+    }
+    let headers = res.headers().clone();
+    let text = res.text().await?;
+    if !status.is_success() {
+        return Err(ApiError::from_body(status, &text).into());
+    }
+    let user: User = serde_json::from_str(&text)
+        .map_err(|e| anyhow::anyhow!("unexpected response from {url}: {e}"))?;
+
+    Ok(AuthStatus {
+        login: user.login,
+        scopes: header_value(&headers, "x-oauth-scopes"),
+        accepted_scopes: header_value(&headers, "x-accepted-oauth-scopes"),
+    })
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers.get(name).and_then(|v| v.to_str().ok()).map(|v| v.to_string())
+}
+
+/// Status as JSON, or human readable with one fact per line
+pub fn render_auth_status(status: &AuthStatus, base_url: &str, format: OutputFormat) -> anyhow::Result<String> {
+    if format == OutputFormat::Json {
+        return Ok(serde_json::to_string_pretty(status)? + "\n");
+    }
+    let scopes = |scopes: &Option<String>| match scopes.as_deref() {
+        Some("") => "(none)".to_string(),
+        Some(scopes) => scopes.to_string(),
+        None => "(not reported; fine-grained or GitHub App token)".to_string(),
+    };
+    Ok(format!(
+        "Logged in to {base_url} as {}\nToken scopes: {}\nAccepted scopes: {}\n",
+        status.login,
+        scopes(&status.scopes),
+        scopes(&status.accepted_scopes),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github_api;
+    use wiremock::matchers::{header, method, path};
+
+    fn client(server: &wiremock::MockServer) -> GithubClient {
+        GithubClient::new(&server.uri(), "t0ken", &github_api::ClientOptions::default()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn reports_login_and_scopes() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(method("GET"))
+            .and(path("/user"))
+            .and(header("authorization", "Bearer t0ken"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(200)
+                    .insert_header("X-OAuth-Scopes", "repo, workflow")
+                    .insert_header("X-Accepted-OAuth-Scopes", "")
+                    .set_body_string(r#"{"login":"octocat","id":1}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let status = auth_status(&client(&server)).await.unwrap();
+        assert_eq!(
+            render_auth_status(&status, "https://api.github.com", OutputFormat::Text).unwrap(),
+            "Logged in to https://api.github.com as octocat\nToken scopes: repo, workflow\nAccepted scopes: (none)\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn maps_401_to_invalid_token() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(method("GET"))
+            .and(path("/user"))
+            .respond_with(wiremock::ResponseTemplate::new(401).set_body_string(r#"{"message":"Bad credentials"}"#))
+            .mount(&server)
+            .await;
+
+        let err = auth_status(&client(&server)).await.unwrap_err();
+        assert!(err.to_string().contains("invalid or expired token"));
+        assert_eq!(github_api::exit_code(&err), exitcode::NOPERM);
+    }
+}
@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Entries not stored again for this long are deleted
+pub const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// API responses stored on disk with their `ETag`, to revalidate with `If-None-Match`.
+///
+/// Entries are keyed by a SHA-256 of URL, `Accept` header and token, so that responses seen with one
+/// token are never served to another. The token itself is not stored. The directory and the files
+/// are private to the user, as they hold API responses of private repositories too.
+/// The first store of a process prunes the entries older than [`MAX_AGE`].
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+/// Cached response body and the `ETag` it was served with
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct CachedResponse {
+    url: String,
+    pub(crate) etag: String,
+    pub(crate) body: String,
+}
+
+impl ResponseCache {
+    pub fn new(dir: &Path) -> Self {
+        Self { dir: dir.to_path_buf() }
+    }
+
+    /// `gha/responses` under the user's cache directory, e.g. `~/.cache/gha/responses`
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("gha").join("responses"))
+    }
+
+    fn path(&self, url: &str, accept: &str, token: &str) -> PathBuf {
+        let mut hasher = Sha256::new();
+        for part in [url, accept, token] {
+            // length-prefixed, so that no two keys hash the same bytes
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+        let hex: String = hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect();
+        self.dir.join(format!("{hex}.json"))
+    }
+
+    /// The stored response of `url`, if any
+    pub(crate) fn load(&self, url: &str, accept: &str, token: &str) -> Option<CachedResponse> {
+        let text = std::fs::read_to_string(self.path(url, accept, token)).ok()?;
+        serde_json::from_str::<CachedResponse>(&text).ok().filter(|cached| cached.url == url)
+    }
+
+    /// Store the response of `url`; failures only cost the next request its shortcut
+    pub(crate) fn store(&self, url: &str, accept: &str, token: &str, etag: &str, body: &str) {
+        let cached = CachedResponse { url: url.to_string(), etag: etag.to_string(), body: body.to_string() };
+        let path = self.path(url, accept, token);
+        let result = create_private_dir(&self.dir).and_then(|()| {
+            let mut file = std::fs::OpenOptions::new();
+            file.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut file, 0o600);
+            let mut file = file.open(&path)?;
+            // also tightens files of older versions, which the mode above does not touch
+            #[cfg(unix)]
+            file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+            file.write_all(serde_json::to_string(&cached).unwrap_or_default().as_bytes())
+        });
+        if let Err(e) = result {
+            tracing::debug!("Caching the response of {url} in {} failed: {e}", path.display());
+        }
+        static PRUNED: std::sync::Once = std::sync::Once::new();
+        PRUNED.call_once(|| self.prune(MAX_AGE));
+    }
+
+    /// Delete the entries last stored more than `max_age` ago, including those of older key schemes
+    pub fn prune(&self, max_age: Duration) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let expired = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age > max_age));
+            if expired
+                && path.extension().is_some_and(|ext| ext == "json")
+                && let Err(e) = std::fs::remove_file(&path)
+            {
+                tracing::debug!("Pruning {} from the cache failed: {e}", path.display());
+            }
+        }
+    }
+}
+
+/// Create `dir` and its parents; `dir` itself is accessible to the user only
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    if let Some(parent) = dir.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)?;
+    #[cfg(unix)]
+    std::fs::set_permissions(dir, std::os::unix::fs::PermissionsExt::from_mode(0o700))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_entries_by_url_accept_and_token() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let cache = ResponseCache::new(&temp.path().join("responses"));
+        let url = "https://api.github.com/repos/owner/repo/actions/workflows";
+        cache.store(url, "json", "t0ken", "\"v1\"", "[]");
+
+        let cached = cache.load(url, "json", "t0ken").unwrap();
+        assert_eq!((cached.etag.as_str(), cached.body.as_str()), ("\"v1\"", "[]"));
+        assert!(cache.load(url, "json", "other-token").is_none());
+        assert!(cache.load(url, "raw", "t0ken").is_none());
+        assert!(cache.load(&format!("{url}?page=2"), "json", "t0ken").is_none());
+    }
+
+    #[test]
+    fn file_names_are_a_stable_hash_of_the_key() {
+        let cache = ResponseCache::new(Path::new("responses"));
+        assert_eq!(
+            cache.path("https://api.github.com/", "json", "t0ken"),
+            Path::new("responses/39fbda70d403e21eba0c391affe04eb6f197ab170ebc8d31f93e8b4012f931ed.json")
+        );
+        assert_ne!(cache.path("ab", "c", ""), cache.path("a", "bc", ""));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn entries_are_private_to_the_user() {
+        use std::os::unix::fs::PermissionsExt;
+        let temp = assert_fs::TempDir::new().unwrap();
+        let dir = temp.path().join("gha/responses");
+        let cache = ResponseCache::new(&dir);
+        // as left behind by older versions
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let path = cache.path("https://api.github.com/", "json", "t0ken");
+        std::fs::write(&path, "{}").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        cache.store("https://api.github.com/", "json", "t0ken", "\"v1\"", "{}");
+
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&dir), 0o700);
+        assert_eq!(mode(&path), 0o600);
+    }
+
+    #[test]
+    fn prune_deletes_only_old_entries() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let cache = ResponseCache::new(temp.path());
+        cache.store("https://api.github.com/a", "json", "t0ken", "\"v1\"", "{}");
+        std::fs::write(temp.path().join("notes.txt"), "kept").unwrap();
+
+        cache.prune(MAX_AGE);
+        assert!(cache.load("https://api.github.com/a", "json", "t0ken").is_some());
+        std::thread::sleep(Duration::from_millis(20));
+        cache.prune(Duration::from_millis(10));
+        assert!(cache.load("https://api.github.com/a", "json", "t0ken").is_none());
+        assert!(temp.path().join("notes.txt").exists());
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::Context;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the repository-local config file, looked up from the current directory upwards
+pub const REPO_CONFIG_FILE: &str = ".gha.toml";
+
+/// Defaults for command line options, read from TOML config files.
+///
+/// Values only replace built-in defaults: command line flags and environment variables win.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Repository in the form "owner/repo"
+    pub repo: Option<String>,
+    /// Ref to dispatch on
+    pub r#ref: Option<String>,
+    /// Git remote used to detect the repository
+    pub remote: Option<String>,
+    /// Base URL of the GitHub REST API
+    pub api_url: Option<String>,
+    /// Dispatch mode: "curl", "make" or "call"
+    pub mode: Option<String>,
+}
+
+impl Config {
+    /// Parse one config file
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// Values of `self`, with the unset ones taken from `fallback`
+    pub fn or(self, fallback: Config) -> Config {
+        Config {
+            repo: self.repo.or(fallback.repo),
+            r#ref: self.r#ref.or(fallback.r#ref),
+            remote: self.remote.or(fallback.remote),
+            api_url: self.api_url.or(fallback.api_url),
+            mode: self.mode.or(fallback.mode),
+        }
+    }
+
+    /// Load the files that exist, the repository one taking precedence over the user one.
+    /// Returns the merged config and the files it was read from.
+    pub fn load(user: Option<&Path>, repo: Option<&Path>) -> anyhow::Result<(Config, Vec<PathBuf>)> {
+        let mut config = Config::default();
+        let mut files = Vec::new();
+        for path in [repo, user].into_iter().flatten() {
+            if path.is_file() {
+                config = config.or(Config::from_file(path)?);
+                files.push(path.to_path_buf());
+            }
+        }
+        Ok((config, files))
+    }
+}
+
+/// `$XDG_CONFIG_HOME/gha/config.toml`, or `~/.config/gha/config.toml`
+pub fn user_config_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))?;
+    Some(config_home.join("gha").join("config.toml"))
+}
+
+/// Nearest [`REPO_CONFIG_FILE`] in `start` or its ancestors
+pub fn find_repo_config(start: &Path) -> Option<PathBuf> {
+    start.ancestors().map(|dir| dir.join(REPO_CONFIG_FILE)).find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::TempDir;
+
+    #[test]
+    fn repo_config_overrides_user_config() {
+        let temp = TempDir::new().unwrap();
+        let user = temp.path().join("config.toml");
+        let repo = temp.path().join(REPO_CONFIG_FILE);
+        fs::write(&user, "repo = \"user/repo\"\nmode = \"call\"\n").unwrap();
+        fs::write(&repo, "repo = \"local/repo\"\n").unwrap();
+
+        let (config, files) = Config::load(Some(&user), Some(&repo)).unwrap();
+        assert_eq!(config.repo.as_deref(), Some("local/repo"));
+        assert_eq!(config.mode.as_deref(), Some("call"));
+        assert_eq!(files, [repo, user]);
+    }
+
+    #[test]
+    fn missing_files_give_empty_config() {
+        let temp = TempDir::new().unwrap();
+        let (config, files) = Config::load(Some(&temp.path().join("nope.toml")), None).unwrap();
+        assert_eq!(config, Config::default());
+        assert!(files.is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(REPO_CONFIG_FILE);
+        fs::write(&path, "repository = \"owner/repo\"\n").unwrap();
+        let err = Config::from_file(&path).unwrap_err();
+        assert!(format!("{err:#}").contains("unknown field"));
+    }
+
+    #[test]
+    fn finds_nearest_repo_config() {
+        let temp = TempDir::new().unwrap();
+        let nested = temp.path().join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(temp.path().join(REPO_CONFIG_FILE), "").unwrap();
+        assert_eq!(find_repo_config(&nested), Some(temp.path().join(REPO_CONFIG_FILE)));
+    }
+}
@@ -0,0 +1,1439 @@
+use crate::gen_client::WorkflowInfo;
+use crate::github_api::{self, GithubClient};
+use crate::output::{paint, OutputFormat, Tone};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::info;
+
+/// Default upper bound of dispatch requests in flight at once, see [`DispatchOptions::concurrency`]
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Most top-level inputs GitHub accepts in a `workflow_dispatch` request
+pub(crate) const MAX_INPUTS: usize = 10;
+
+/// Serialized inputs above this size are rejected by GitHub
+const MAX_INPUTS_BYTES: usize = 65_535;
+
+#[derive(Serialize)]
+struct DispatchPayload {
+    r#ref: String,
+    /// In the order of the `--arg`s (serde_json's `preserve_order`)
+    inputs: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct RepositoryDispatchPayload {
+    event_type: String,
+    client_payload: serde_json::Map<String, serde_json::Value>,
+}
+
+/// How dispatch requests are carried out
+#[derive(Debug, Clone)]
+pub struct DispatchOptions {
+    /// `"curl"` prints a curl command, `"make"` prints it as a Makefile recipe
+    /// and `"call"` sends the request to GitHub
+    pub mode: String,
+    /// `Json` prints the request's URL and payload instead of a command
+    pub format: OutputFormat,
+    /// In "call" mode, log the request instead of sending it
+    pub dry_run: bool,
+    /// Extra request headers; they replace default headers of the same name
+    pub headers: Vec<(String, String)>,
+    /// In "call" mode, check that the ref is an existing branch or tag before dispatching
+    pub verify_ref: bool,
+    /// Fail instead of warning when the inputs exceed GitHub's limits
+    pub strict: bool,
+    /// In the printing modes, append the output to this file instead of stdout; `-` is stdout
+    pub output: Option<PathBuf>,
+    /// In the printing modes, do not warn that the printed command contains the token
+    pub show_token: bool,
+    /// Keep the trailing newline of `@file` values instead of trimming it
+    pub no_trim: bool,
+    /// In the printing modes, write the headers to this curl config file and print `curl -K <file>`,
+    /// keeping the token out of the command line
+    pub curl_config: Option<PathBuf>,
+    /// Upper bound of dispatch requests in flight at once in "call" mode
+    pub concurrency: usize,
+    /// In "call" mode, fail before dispatching when the workflow is not `active`
+    pub fail_on_disabled: bool,
+}
+
+impl DispatchOptions {
+    /// Options for `mode` with text output
+    pub fn new(mode: &str) -> Self {
+        Self {
+            mode: mode.to_string(),
+            format: OutputFormat::Text,
+            dry_run: false,
+            headers: Vec::new(),
+            verify_ref: false,
+            strict: false,
+            output: None,
+            show_token: false,
+            no_trim: false,
+            curl_config: None,
+            concurrency: DEFAULT_CONCURRENCY,
+            fail_on_disabled: false,
+        }
+    }
+}
+
+/// What [`workflow_dispatch`] did with the request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DispatchOutcome {
+    /// Printed as a curl command or as JSON ("curl" and "make" modes)
+    Printed,
+    /// Logged instead of sent ("call" mode with `dry_run`)
+    DryRun,
+    /// Accepted by GitHub; the web URL of the run when the response reports it
+    Dispatched { run_url: Option<String> },
+}
+
+/// Why [`workflow_dispatch`] failed
+#[derive(Debug, thiserror::Error)]
+pub enum DispatchError {
+    /// An `--arg` is malformed or its file is unreadable
+    #[error("{0}")]
+    InvalidArg(String),
+    /// The inputs exceed GitHub's limits, with `strict`
+    #[error("{}", .0.join("; "))]
+    InputLimits(Vec<String>),
+    /// `verify_ref` found no such branch or tag
+    #[error("ref '{}' not found in {repo}: no such branch or tag", .r#ref)]
+    RefNotFound { repo: String, r#ref: String },
+    /// `fail_on_disabled` found the workflow in another state than `active`
+    #[error("workflow {workflow} is {state}; enable it first with `gha workflow enable --workflow {workflow}` or on the Actions tab of {repo}")]
+    WorkflowDisabled { repo: String, workflow: String, state: String },
+    /// Mode other than "curl", "make", "call" and "act"
+    #[error("Invalid mode: {0}")]
+    InvalidMode(String),
+    /// GitHub rejected a request
+    #[error(transparent)]
+    Api(#[from] github_api::ApiError),
+    /// A request could not be sent or timed out
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    /// Anything else, e.g. an unwritable output file
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+impl From<anyhow::Error> for DispatchError {
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<github_api::ApiError>() {
+            Ok(api_error) => return DispatchError::Api(api_error),
+            Err(e) => e,
+        };
+        match e.downcast::<reqwest::Error>() {
+            Ok(request_error) => DispatchError::Request(request_error),
+            Err(e) => DispatchError::Other(e),
+        }
+    }
+}
+
+impl DispatchError {
+    /// Process exit code describing the failure
+    pub fn exit_code(&self) -> exitcode::ExitCode {
+        match self {
+            DispatchError::InvalidArg(_) | DispatchError::InvalidMode(_) => exitcode::USAGE,
+            DispatchError::InputLimits(_) => exitcode::DATAERR,
+            DispatchError::RefNotFound { .. } | DispatchError::WorkflowDisabled { .. } => exitcode::UNAVAILABLE,
+            DispatchError::Api(e) => e.exit_code(),
+            DispatchError::Request(e) => github_api::request_exit_code(e),
+            DispatchError::Other(e) => github_api::exit_code(e),
+        }
+    }
+}
+
+/// Dispatch `workflow` on `r#ref` with `args` as inputs; see [`DispatchOptions`] for what happens with the request.
+pub async fn workflow_dispatch(
+    gh: &GithubClient,
+    repo: &str,
+    workflow: &str,
+    r#ref: &str,
+    args: &[String],
+    options: &DispatchOptions,
+) -> Result<DispatchOutcome, DispatchError> {
+    check_mode(options)?;
+    let inputs = parse_args_trimmed(args, !options.no_trim).map_err(|e| DispatchError::InvalidArg(e.to_string()))?;
+    dispatch_inputs(gh, repo, workflow, r#ref, inputs, options).await
+}
+
+fn check_mode(options: &DispatchOptions) -> Result<(), DispatchError> {
+    if !matches!(options.mode.as_str(), "curl" | "make" | "call" | "act") {
+        return Err(DispatchError::InvalidMode(options.mode.clone()));
+    }
+    Ok(())
+}
+
+/// [`workflow_dispatch`] with the already parsed `inputs`
+async fn dispatch_inputs(
+    gh: &GithubClient,
+    repo: &str,
+    workflow: &str,
+    r#ref: &str,
+    inputs: serde_json::Map<String, serde_json::Value>,
+    options: &DispatchOptions,
+) -> Result<DispatchOutcome, DispatchError> {
+    check_mode(options)?;
+    let problems = input_limit_problems(&inputs);
+    if options.strict && !problems.is_empty() {
+        return Err(DispatchError::InputLimits(problems));
+    }
+    for problem in &problems {
+        tracing::warn!("{problem}");
+    }
+    if options.mode == "act" {
+        emit(options.output.as_deref(), &act_command(workflow, &inputs))?;
+        return Ok(DispatchOutcome::Printed);
+    }
+    let resolved = resolve_pr_ref(gh, repo, r#ref, options.mode == "call").await?;
+    let r#ref = resolved.as_str();
+
+    if options.mode == "call" && options.verify_ref && !looks_like_sha(r#ref) && !ref_exists(gh, repo, r#ref).await? {
+        return Err(DispatchError::RefNotFound { repo: repo.to_string(), r#ref: r#ref.to_string() });
+    }
+    if options.mode == "call" && options.fail_on_disabled {
+        let state = crate::workflows::get_workflow(gh, repo, workflow).await?.state;
+        if state != "active" {
+            return Err(DispatchError::WorkflowDisabled { repo: repo.to_string(), workflow: workflow.to_string(), state });
+        }
+    }
+
+    let payload = DispatchPayload {
+        r#ref: r#ref.to_string(),
+        inputs,
+    };
+
+    let url = format!(
+        "{}/repos/{}/actions/workflows/{}/dispatches",
+        gh.base_url(), repo, workflow
+    );
+
+    let Some(res) = post_or_print(gh, &url, &payload, options).await? else {
+        return Ok(if options.mode == "call" { DispatchOutcome::DryRun } else { DispatchOutcome::Printed });
+    };
+    let response_status = res.status();
+    let text = res.text().await?;
+    if !response_status.is_success() {
+        return Err(dispatch_error(response_status, &text, repo, workflow).into());
+    }
+    info!("Workflow dispatch successful");
+
+    // 204 has no body; a 200 may describe the started run
+    let run_url = serde_json::from_str::<serde_json::Value>(&text)
+        .ok()
+        .and_then(|body| body.get("html_url")?.as_str().map(str::to_string));
+    Ok(DispatchOutcome::Dispatched { run_url })
+}
+
+/// Whether `r#ref` resolves to a branch or tag of `repo`; a full `refs/...` name is looked up as is
+pub async fn ref_exists(gh: &GithubClient, repo: &str, r#ref: &str) -> anyhow::Result<bool> {
+    let candidates = match r#ref.strip_prefix("refs/") {
+        Some(full) => vec![full.to_string()],
+        None => vec![format!("heads/{}", r#ref), format!("tags/{}", r#ref)],
+    };
+    for candidate in candidates {
+        let url = format!("{}/repos/{repo}/git/ref/{candidate}", gh.base_url());
+        match gh.get_json::<serde_json::Value>(&url).await {
+            Ok(_) => return Ok(true),
+            Err(e) if e.downcast_ref::<github_api::ApiError>().is_some_and(|e| e.status == reqwest::StatusCode::NOT_FOUND) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(false)
+}
+
+/// Reasons why GitHub would reject `inputs`, rather than waiting for its 422
+pub fn input_limit_problems(inputs: &serde_json::Map<String, serde_json::Value>) -> Vec<String> {
+    let mut problems = Vec::new();
+    if inputs.len() > MAX_INPUTS {
+        problems.push(format!("{} inputs given, but GitHub accepts at most {MAX_INPUTS}", inputs.len()));
+    }
+    let size = serde_json::to_string(inputs).map(|json| json.len()).unwrap_or_default();
+    if size > MAX_INPUTS_BYTES {
+        problems.push(format!("inputs are {size} bytes, but GitHub accepts at most {MAX_INPUTS_BYTES}"));
+    }
+    problems
+}
+
+/// Pull request number of a `pr/123` or `#123` ref
+fn pr_number(r#ref: &str) -> Option<u64> {
+    r#ref.strip_prefix("pr/").or_else(|| r#ref.strip_prefix('#'))?.parse().ok()
+}
+
+#[derive(Deserialize)]
+struct PullRequest {
+    head: PullRequestHead,
+}
+
+#[derive(Deserialize)]
+struct PullRequestHead {
+    r#ref: String,
+}
+
+/// The head branch of the pull request when `r#ref` is `pr/123` or `#123`, otherwise `r#ref` itself.
+/// With `required`, a failed lookup is an error; otherwise it is warned about and the ref is kept as given.
+pub async fn resolve_pr_ref(gh: &GithubClient, repo: &str, r#ref: &str, required: bool) -> anyhow::Result<String> {
+    let Some(number) = pr_number(r#ref) else {
+        return Ok(r#ref.to_string());
+    };
+    let url = format!("{}/repos/{repo}/pulls/{number}", gh.base_url());
+    match gh.get_json::<PullRequest>(&url).await {
+        Ok(pr) => {
+            info!("Pull request #{number} of {repo} has head ref {}", pr.head.r#ref);
+            Ok(pr.head.r#ref)
+        }
+        Err(e) if required => Err(e.context(format!("resolving pull request #{number} of {repo} failed"))),
+        Err(e) => {
+            tracing::warn!("Resolving pull request #{number} of {repo} failed, keeping '{}': {e}", r#ref);
+            Ok(r#ref.to_string())
+        }
+    }
+}
+
+/// A full commit SHA, which GitHub resolves without a branch or tag
+fn looks_like_sha(r#ref: &str) -> bool {
+    matches!(r#ref.len(), 40 | 64) && r#ref.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// What happened to the dispatch on one ref
+#[derive(Debug)]
+pub enum RefOutcome {
+    /// Request printed or accepted by GitHub
+    Dispatched(DispatchOutcome),
+    /// Request failed
+    Failed(DispatchError),
+    /// Not attempted (or aborted) because of `--fail-fast`
+    Skipped,
+}
+
+impl From<Result<DispatchOutcome, DispatchError>> for RefOutcome {
+    fn from(result: Result<DispatchOutcome, DispatchError>) -> Self {
+        match result {
+            Ok(outcome) => RefOutcome::Dispatched(outcome),
+            Err(e) => RefOutcome::Failed(e),
+        }
+    }
+}
+
+/// Per-ref outcomes of a multi-ref dispatch, in the order the refs were given
+#[derive(Debug)]
+pub struct DispatchSummary {
+    /// `(ref, outcome)` pairs
+    pub results: Vec<(String, RefOutcome)>,
+}
+
+impl DispatchSummary {
+    /// `exitcode::OK` only when every ref was dispatched; otherwise the code of the first failure
+    pub fn exit_code(&self) -> exitcode::ExitCode {
+        if self.results.iter().all(|(_, o)| matches!(o, RefOutcome::Dispatched(_))) {
+            return exitcode::OK;
+        }
+        self.results
+            .iter()
+            .find_map(|(_, o)| match o {
+                RefOutcome::Failed(e) => Some(e.exit_code()),
+                _ => None,
+            })
+            .unwrap_or(exitcode::SOFTWARE)
+    }
+
+    /// Counts line followed by one line per ref, with colored outcomes when `color` is set
+    pub fn render(&self, color: bool) -> String {
+        let count = |f: fn(&RefOutcome) -> bool| self.results.iter().filter(|(_, o)| f(o)).count();
+        let mut out = format!(
+            "Dispatch summary: {} dispatched, {} failed, {} skipped",
+            count(|o| matches!(o, RefOutcome::Dispatched(_))),
+            count(|o| matches!(o, RefOutcome::Failed(_))),
+            count(|o| matches!(o, RefOutcome::Skipped)),
+        );
+        for (r#ref, outcome) in &self.results {
+            match outcome {
+                RefOutcome::Dispatched(_) => out.push_str(&format!("\n  {ref}: {}", paint("dispatched", Tone::Success, color))),
+                RefOutcome::Failed(e) => out.push_str(&format!("\n  {ref}: {}: {e}", paint("failed", Tone::Failure, color))),
+                RefOutcome::Skipped => out.push_str(&format!("\n  {ref}: {}", paint("skipped", Tone::Warning, color))),
+            }
+        }
+        out
+    }
+
+    /// One object per ref with its outcome, plus the run id and URL where `run_of` knows the run,
+    /// or the run URL reported by the dispatch response
+    pub fn to_json(&self, run_of: impl Fn(&str) -> Option<(u64, String)>) -> serde_json::Value {
+        let results = self
+            .results
+            .iter()
+            .map(|(r#ref, outcome)| {
+                let mut obj = serde_json::Map::new();
+                obj.insert("ref".into(), r#ref.clone().into());
+                let status = match outcome {
+                    RefOutcome::Dispatched(_) => "dispatched",
+                    RefOutcome::Failed(e) => {
+                        obj.insert("error".into(), e.to_string().into());
+                        "failed"
+                    }
+                    RefOutcome::Skipped => "skipped",
+                };
+                obj.insert("status".into(), status.into());
+                if let Some((run_id, run_url)) = run_of(r#ref) {
+                    obj.insert("run_id".into(), run_id.into());
+                    obj.insert("run_url".into(), run_url.into());
+                } else if let RefOutcome::Dispatched(DispatchOutcome::Dispatched { run_url: Some(run_url) }) = outcome {
+                    obj.insert("run_url".into(), run_url.clone().into());
+                }
+                serde_json::Value::Object(obj)
+            })
+            .collect();
+        serde_json::Value::Array(results)
+    }
+}
+
+/// Dispatch `workflow` on each of `refs`, see [`workflow_dispatch`].
+///
+/// In "call" mode the requests run concurrently over the shared client, at most
+/// [`DispatchOptions::concurrency`] at a time. With `fail_fast`, the first failure stops the remaining refs.
+pub async fn workflow_dispatch_refs(
+    gh: &GithubClient,
+    repo: &str,
+    workflow: &str,
+    refs: &[String],
+    args: &[String],
+    options: &DispatchOptions,
+    fail_fast: bool,
+) -> DispatchSummary {
+    // `@-` can only be read once, so the args are parsed before fanning out
+    let inputs = match parse_args_trimmed(args, !options.no_trim) {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            let results = refs.iter().map(|r| (r.clone(), RefOutcome::Failed(DispatchError::InvalidArg(e.to_string())))).collect();
+            return DispatchSummary { results };
+        }
+    };
+    let targets = [(workflow.to_string(), inputs)];
+    let (_, summary) = workflow_dispatch_many(gh, repo, &targets, refs, options, fail_fast).await.swap_remove(0);
+    summary
+}
+
+/// Dispatch each `(workflow, inputs)` of `targets` on each of `refs`, like [`workflow_dispatch_refs`]
+/// but with the [`DispatchOptions::concurrency`] bound and `fail_fast` shared by all workflows.
+/// The inputs are parsed already, see [`parse_args_trimmed`], so that `@-` is read only once.
+///
+/// Returns one summary per workflow, in the order of `targets`.
+pub async fn workflow_dispatch_many(
+    gh: &GithubClient,
+    repo: &str,
+    targets: &[(String, serde_json::Map<String, serde_json::Value>)],
+    refs: &[String],
+    options: &DispatchOptions,
+    fail_fast: bool,
+) -> Vec<(String, DispatchSummary)> {
+    let mut outcomes: Vec<Vec<RefOutcome>> = targets.iter().map(|_| refs.iter().map(|_| RefOutcome::Skipped).collect()).collect();
+
+    if options.mode != "call" {
+        // printing modes keep the output in the order of the workflows and refs
+        'targets: for (w, (workflow, inputs)) in targets.iter().enumerate() {
+            for (i, r#ref) in refs.iter().enumerate() {
+                let outcome = RefOutcome::from(dispatch_inputs(gh, repo, workflow, r#ref, inputs.clone(), options).await);
+                let failed = matches!(outcome, RefOutcome::Failed(_));
+                outcomes[w][i] = outcome;
+                if failed && fail_fast {
+                    break 'targets;
+                }
+            }
+        }
+    } else {
+        let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        for (w, (workflow, inputs)) in targets.iter().enumerate() {
+            for (i, r#ref) in refs.iter().enumerate() {
+                let (gh, semaphore, options) = (gh.clone(), semaphore.clone(), options.clone());
+                let (repo, workflow, r#ref, inputs) = (repo.to_string(), workflow.clone(), r#ref.clone(), inputs.clone());
+                tasks.spawn(async move {
+                    let _permit = semaphore.acquire_owned().await;
+                    (w, i, dispatch_inputs(&gh, &repo, &workflow, &r#ref, inputs, &options).await)
+                });
+            }
+        }
+        while let Some(joined) = tasks.join_next().await {
+            // aborted tasks stay `Skipped`
+            let Ok((w, i, result)) = joined else { continue };
+            let outcome = RefOutcome::from(result);
+            if matches!(outcome, RefOutcome::Failed(_)) && fail_fast {
+                tasks.abort_all();
+            }
+            outcomes[w][i] = outcome;
+        }
+    }
+
+    targets
+        .iter()
+        .zip(outcomes)
+        .map(|((workflow, _), outcomes)| {
+            (workflow.clone(), DispatchSummary { results: refs.iter().cloned().zip(outcomes).collect() })
+        })
+        .collect()
+}
+
+/// `exitcode::OK` only when every workflow was dispatched on every ref; otherwise the code of the first failing workflow
+pub fn combined_exit_code(summaries: &[(String, DispatchSummary)]) -> exitcode::ExitCode {
+    summaries
+        .iter()
+        .map(|(_, summary)| summary.exit_code())
+        .find(|code| *code != exitcode::OK)
+        .unwrap_or(exitcode::OK)
+}
+
+/// The `inputs` that `info` declares; others would make GitHub reject the dispatch
+pub fn applicable_inputs(
+    inputs: &serde_json::Map<String, serde_json::Value>,
+    info: &WorkflowInfo,
+) -> serde_json::Map<String, serde_json::Value> {
+    inputs
+        .iter()
+        .filter(|(name, _)| {
+            let declared = info.inputs.iter().any(|input| &input.name == *name);
+            if !declared {
+                tracing::debug!("Not passing {name} to {}, which has no such input", info.file);
+            }
+            declared
+        })
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
+
+/// Send a `repository_dispatch` event of `event_type`; `options` work as in [`workflow_dispatch`].
+///
+/// The client payload is `input_json` (inline JSON object or `@file`) overlaid with `args`.
+pub async fn repository_dispatch(
+    gh: &GithubClient,
+    repo: &str,
+    event_type: &str,
+    input_json: Option<&str>,
+    args: &[String],
+    options: &DispatchOptions,
+) -> anyhow::Result<()> {
+    let payload = repository_dispatch_payload(event_type, input_json, args, !options.no_trim)?;
+    let url = repository_dispatch_url(gh.base_url(), repo);
+
+    if let Some(res) = post_or_print(gh, &url, &payload, options).await? {
+        let response_status = res.status();
+        if !response_status.is_success() {
+            let text = res.text().await?;
+            return Err(github_api::ApiError::from_body(response_status, &text).into());
+        }
+
+        info!("Repository dispatch of '{event_type}' successful");
+    }
+
+    Ok(())
+}
+
+fn repository_dispatch_url(base_url: &str, repo: &str) -> String {
+    format!("{base_url}/repos/{repo}/dispatches")
+}
+
+/// Build the client payload from `--input-json` overlaid with `--arg`s
+fn repository_dispatch_payload(event_type: &str, input_json: Option<&str>, args: &[String], trim: bool) -> anyhow::Result<RepositoryDispatchPayload> {
+    let mut client_payload = match input_json {
+        Some(input_json) => parse_input_json(input_json)?,
+        None => serde_json::Map::new(),
+    };
+    client_payload.extend(parse_args_from(args, std::io::stdin(), trim)?);
+    Ok(RepositoryDispatchPayload {
+        event_type: event_type.to_string(),
+        client_payload,
+    })
+}
+
+/// Parse `--arg` values into inputs:
+/// - `name=value` is the string `value`
+/// - `name=@file` is the content of `file` without a single trailing newline,
+///   and `name=@-` is read from stdin (at most once)
+/// - `name=@base64:file` is the base64-encoded content of `file`, for binary data
+/// - `name:=json` is a typed value such as `true`, `42` or `["a", "b"]`
+pub fn parse_args(args: &[String]) -> anyhow::Result<serde_json::Map<String, serde_json::Value>> {
+    parse_args_trimmed(args, true)
+}
+
+/// [`parse_args`], keeping the trailing newline of `@file` values unless `trim`
+pub fn parse_args_trimmed(args: &[String], trim: bool) -> anyhow::Result<serde_json::Map<String, serde_json::Value>> {
+    parse_args_from(args, std::io::stdin(), trim)
+}
+
+/// [`parse_args`] with `@-` read from `stdin`; `trim` drops one trailing newline of `@file` values
+fn parse_args_from(args: &[String], mut stdin: impl std::io::Read, trim: bool) -> anyhow::Result<serde_json::Map<String, serde_json::Value>> {
+    let mut inputs = serde_json::Map::new();
+    let mut stdin_used = false;
+
+    for arg in args {
+        let Some((key, value)) = arg.split_once('=') else {
+            anyhow::bail!("Invalid arg format: {arg}");
+        };
+        let (key, typed) = match key.strip_suffix(':') {
+            Some(key) => (key, true),
+            None => (key, false),
+        };
+        if key.is_empty() {
+            anyhow::bail!("Invalid arg format, missing input name: {arg}");
+        }
+        let text = match value.strip_prefix('@') {
+            Some("-") if stdin_used => anyhow::bail!("Only one --arg can be read from stdin: {arg}"),
+            Some("-") => {
+                stdin_used = true;
+                let mut contents = String::new();
+                stdin.read_to_string(&mut contents).map_err(|e| anyhow::anyhow!("failed to read stdin for {key}: {e}"))?;
+                contents
+            }
+            Some(spec) if let Some(file_path) = spec.strip_prefix("base64:") => {
+                let bytes = fs::read(file_path).map_err(|e| anyhow::anyhow!("failed to read {file_path} for {key}: {e}"))?;
+                base64::engine::general_purpose::STANDARD.encode(bytes)
+            }
+            Some(file_path) => {
+                let contents = fs::read_to_string(file_path).map_err(|e| anyhow::anyhow!("failed to read {file_path} for {key}: {e}"))?;
+                match contents.strip_suffix('\n') {
+                    Some(trimmed) if trim => trimmed.strip_suffix('\r').unwrap_or(trimmed).to_string(),
+                    _ => contents,
+                }
+            }
+            None => value.to_string(),
+        };
+        let val = if typed {
+            serde_json::from_str(&text).map_err(|e| anyhow::anyhow!("Invalid JSON value for {key}: {e}"))?
+        } else {
+            serde_json::Value::String(text)
+        };
+        inputs.insert(key.to_string(), val);
+    }
+
+    Ok(inputs)
+}
+
+/// `--arg`s for the top-level table of the TOML, YAML or JSON file at `path`, detected by its extension.
+/// Booleans and numbers become strings unless `typed`; nested tables, lists and nulls are rejected.
+pub fn input_file_args(path: &Path, typed: bool) -> anyhow::Result<Vec<String>> {
+    let text = fs::read_to_string(path).map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))?;
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    let parsed: anyhow::Result<serde_json::Value> = match extension.as_str() {
+        "toml" => toml::from_str::<toml::Table>(&text).map(toml_to_json).map_err(Into::into),
+        "yaml" | "yml" => serde_yml::from_str(&text).map_err(Into::into),
+        "json" => serde_json::from_str(&text).map_err(Into::into),
+        _ => anyhow::bail!("cannot tell the format of {}; use a .toml, .yaml, .yml or .json file", path.display()),
+    };
+    let parsed = parsed.map_err(|e| anyhow::anyhow!("failed to parse {}: {e}", path.display()))?;
+    let serde_json::Value::Object(inputs) = parsed else {
+        anyhow::bail!("{} must contain a table of inputs", path.display());
+    };
+    inputs
+        .into_iter()
+        .map(|(key, value)| match value {
+            serde_json::Value::String(_) => Ok(format!("{key}:={value}")),
+            serde_json::Value::Bool(_) | serde_json::Value::Number(_) if typed => Ok(format!("{key}:={value}")),
+            serde_json::Value::Bool(_) | serde_json::Value::Number(_) => Ok(format!("{key}:={}", serde_json::Value::String(value.to_string()))),
+            _ => anyhow::bail!("input '{key}' in {} is {value}; workflow inputs must be strings, numbers or booleans", path.display()),
+        })
+        .collect()
+}
+
+/// The ref and the `--arg`s of a `{"ref": …, "inputs": {…}}` request body given inline or as `@file`,
+/// such as the samples written by `gen --sample-payloads`; the `--arg`s are to be followed by those overriding them.
+pub fn input_json_args(input_json: &str) -> anyhow::Result<(Option<String>, Vec<String>)> {
+    let mut body = parse_input_json(input_json)?;
+    if let Some(key) = body.keys().find(|key| *key != "ref" && *key != "inputs") {
+        anyhow::bail!("unexpected {key:?} in --input-json; expected a request body with \"ref\" and \"inputs\"");
+    }
+    let git_ref = match body.remove("ref") {
+        None => None,
+        Some(serde_json::Value::String(git_ref)) => Some(git_ref),
+        Some(other) => anyhow::bail!("\"ref\" in --input-json must be a string, not {other}"),
+    };
+    let inputs = match body.remove("inputs") {
+        None => serde_json::Map::new(),
+        Some(serde_json::Value::Object(inputs)) => inputs,
+        Some(other) => anyhow::bail!("\"inputs\" in --input-json must be an object, not {other}"),
+    };
+    let args = inputs
+        .into_iter()
+        .map(|(key, value)| match value {
+            serde_json::Value::String(_) | serde_json::Value::Bool(_) | serde_json::Value::Number(_) => Ok(format!("{key}:={value}")),
+            _ => anyhow::bail!("input '{key}' in --input-json is {value}; workflow inputs must be strings, numbers or booleans"),
+        })
+        .collect::<anyhow::Result<_>>()?;
+    Ok((git_ref, args))
+}
+
+/// `table` as JSON; TOML dates and times become strings
+fn toml_to_json(table: toml::Table) -> serde_json::Value {
+    fn convert(value: toml::Value) -> serde_json::Value {
+        match value {
+            toml::Value::String(s) => s.into(),
+            toml::Value::Integer(i) => i.into(),
+            toml::Value::Float(f) => f.into(),
+            toml::Value::Boolean(b) => b.into(),
+            toml::Value::Datetime(d) => d.to_string().into(),
+            toml::Value::Array(values) => values.into_iter().map(convert).collect(),
+            toml::Value::Table(table) => toml_to_json(table),
+        }
+    }
+    table.into_iter().map(|(key, value)| (key, convert(value))).collect::<serde_json::Map<_, _>>().into()
+}
+
+/// `--arg`s for the `KEY=value` lines of the dotenv file at `path`, to be followed by the explicit `--arg`s overriding them.
+/// Keys are lowercased unless `keep_case`; values are taken literally, even when they start with `@`,
+/// and unlike in the `.env` files loaded into the environment, `$VAR` is not substituted.
+pub fn env_file_args(path: &Path, keep_case: bool) -> anyhow::Result<Vec<String>> {
+    let text = fs::read_to_string(path).map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))?;
+    let mut args = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let entry = parse_env_line(line).map_err(|e| anyhow::anyhow!("failed to parse {} line {}: {e}", path.display(), number + 1))?;
+        if let Some((key, value)) = entry {
+            let key = if keep_case { key.to_string() } else { key.to_lowercase() };
+            args.push(format!("{key}:={}", serde_json::Value::String(value)));
+        }
+    }
+    Ok(args)
+}
+
+/// `KEY=value` of a dotenv line, `None` for blank and comment lines.
+/// Values may be 'single quoted' (as is), "double quoted" (with `\n`, `\t`, `\"` and `\\` escapes)
+/// or unquoted up to a ` #` comment.
+fn parse_env_line(line: &str) -> anyhow::Result<Option<(&str, String)>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let line = line.strip_prefix("export ").map(str::trim_start).unwrap_or(line);
+    let Some((key, value)) = line.split_once('=') else {
+        anyhow::bail!("expected KEY=value");
+    };
+    let key = key.trim_end();
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        anyhow::bail!("invalid key {key:?}");
+    }
+    let value = value.trim_start();
+    let (value, rest) = match value.chars().next() {
+        Some('\'') => {
+            let end = value[1..].find('\'').ok_or_else(|| anyhow::anyhow!("unterminated single quote"))?;
+            (value[1..=end].to_string(), &value[end + 2..])
+        }
+        Some('"') => {
+            let mut unescaped = String::new();
+            let mut chars = value[1..].char_indices();
+            let end = loop {
+                match chars.next() {
+                    None => anyhow::bail!("unterminated double quote"),
+                    Some((i, '"')) => break i + 2,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => unescaped.push('\n'),
+                        Some((_, 't')) => unescaped.push('\t'),
+                        Some((_, c)) => unescaped.push(c),
+                        None => anyhow::bail!("unterminated double quote"),
+                    },
+                    Some((_, c)) => unescaped.push(c),
+                }
+            };
+            (unescaped, &value[end..])
+        }
+        _ => {
+            let end = value.find(" #").or_else(|| value.find("\t#")).unwrap_or(value.len());
+            (value[..end].trim_end().to_string(), "")
+        }
+    };
+    let rest = rest.trim_start();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        anyhow::bail!("unexpected {rest:?} after the quoted value");
+    }
+    Ok(Some((key, value)))
+}
+
+/// Expand `${VAR}` in the inline values of `args` with `lookup`; `$$` is a literal `$`.
+/// `@file` and `@-` values are kept as they are, so file contents are never expanded.
+/// Undefined variables are an error, unless `undefined_empty` expands them to nothing.
+pub fn expand_env_args(args: &[String], lookup: impl Fn(&str) -> Option<String>, undefined_empty: bool) -> anyhow::Result<Vec<String>> {
+    args.iter()
+        .map(|arg| match arg.split_once('=') {
+            Some((_, value)) if value.starts_with('@') => Ok(arg.clone()),
+            Some((key, value)) => Ok(format!("{key}={}", expand_env(value, &lookup, undefined_empty).map_err(|e| anyhow::anyhow!("{e} in --arg {arg}"))?)),
+            // reported by parse_args
+            None => Ok(arg.clone()),
+        })
+        .collect()
+}
+
+fn expand_env(value: &str, lookup: &impl Fn(&str) -> Option<String>, undefined_empty: bool) -> anyhow::Result<String> {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        if let Some(after) = rest.strip_prefix("$$") {
+            out.push('$');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let Some(end) = after.find('}') else {
+                anyhow::bail!("Unterminated ${{");
+            };
+            let name = &after[..end];
+            match lookup(name) {
+                Some(value) => out.push_str(&value),
+                None if undefined_empty => {}
+                None => anyhow::bail!("Undefined environment variable {name} (--expand-env-default expands it to nothing)"),
+            }
+            rest = &after[end + 1..];
+        } else {
+            out.push('$');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Parse a JSON object given inline or as `@file`
+pub fn parse_input_json(input_json: &str) -> anyhow::Result<serde_json::Map<String, serde_json::Value>> {
+    let text = match input_json.strip_prefix('@') {
+        Some(file_path) => fs::read_to_string(file_path)?,
+        None => input_json.to_string(),
+    };
+    match serde_json::from_str(&text)? {
+        serde_json::Value::Object(map) => Ok(map),
+        _ => anyhow::bail!("--input-json must be a JSON object"),
+    }
+}
+
+/// Print the POST request as a curl command ("curl" and "make" modes), or send it ("call" mode).
+/// With JSON output, the printing modes print the URL and payload as one JSON line instead.
+/// The response is returned only in "call" mode, unless it is a dry run.
+async fn post_or_print(
+    gh: &GithubClient,
+    url: &str,
+    payload: &impl Serialize,
+    options: &DispatchOptions,
+) -> anyhow::Result<Option<reqwest::Response>> {
+    let token = gh.token();
+    let json_str = serde_json::to_string_pretty(payload)?;
+    let mode = options.mode.as_str();
+
+    if (mode == "curl" || mode == "make") && options.format == OutputFormat::Json {
+        let request = serde_json::json!({"method": "POST", "url": url, "payload": payload});
+        emit(options.output.as_deref(), &request.to_string())?;
+    } else if mode == "curl" || mode == "make" {
+        let escaped_json = json_str.replace('\'', "\\'");
+        let mut headers = curl_headers(token, gh.api_version(), &options.headers);
+        let mut lines = vec!["curl -X POST".to_string()];
+        let mut proxy = gh.proxy();
+        if let Some(config) = &options.curl_config {
+            // the proxy URL may carry credentials too
+            write_curl_config(config, &headers, proxy.take())?;
+            lines.push(format!("-K {}", shell_quote(&config.display().to_string())));
+            headers.clear();
+        }
+        if !options.show_token && !token.is_empty() && headers.iter().any(|(_, value)| value.contains(token)) {
+            static WARNED: std::sync::Once = std::sync::Once::new();
+            WARNED.call_once(|| {
+                tracing::warn!("The printed command contains the GitHub token in plain text; do not share it (--show-token silences this warning)");
+            });
+        }
+        if !options.show_token && proxy.is_some_and(has_credentials) {
+            static WARNED: std::sync::Once = std::sync::Once::new();
+            WARNED.call_once(|| {
+                tracing::warn!("The printed command contains the proxy credentials in plain text; do not share it (--curl-config keeps them off the command line, --show-token silences this warning)");
+            });
+        }
+        if let Some(proxy) = proxy {
+            lines.push(format!("-x {}", shell_quote(proxy)));
+        } else if gh.no_proxy() {
+            lines.push("--noproxy '*'".to_string());
+        }
+        lines.extend(headers.iter().map(|(name, value)| format!("-H '{name}: {value}'")));
+        lines.push(url.to_string());
+        lines.push(format!("-d '{escaped_json}'"));
+        // Makefile recipes are tab-indented
+        let (first, rest) = if mode == "make" { ("\t", "\t  ") } else { ("", "  ") };
+        let command = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| format!("{}{line}", if i == 0 { first } else { rest }))
+            .collect::<Vec<_>>()
+            .join(" \\\n");
+        emit(options.output.as_deref(), &command)?;
+    } else if mode == "call" {
+        let mut request = gh.request(reqwest::Method::POST, url).json(payload);
+        for (name, value) in &options.headers {
+            request = request.header(name, value);
+        }
+        if options.dry_run {
+            let request = request.build()?;
+            info!(
+                "Dry run, not sending:\n{} {}\n{}\n\n{json_str}",
+                request.method(),
+                request.url(),
+                github_api::format_headers_redacted(request.headers())
+            );
+            return Ok(None);
+        }
+        return Ok(Some(gh.execute(request).await?));
+    } else {
+        return Err(anyhow::anyhow!("Invalid mode: {}", mode));
+    }
+
+    Ok(None)
+}
+
+/// `act` command running `workflow` of the local checkout with `inputs` ("act" mode); the ref does not apply
+fn act_command(workflow: &str, inputs: &serde_json::Map<String, serde_json::Value>) -> String {
+    let mut lines = vec!["act workflow_dispatch".to_string(), format!("-W {}", shell_quote(&format!(".github/workflows/{workflow}")))];
+    for (name, value) in inputs {
+        let value = match value {
+            serde_json::Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        lines.push(format!("--input {}", shell_quote(&format!("{name}={value}"))));
+    }
+    lines.join(" \\\n  ")
+}
+
+/// `text` as a single-quoted shell word
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// Whether the proxy URL carries a user name or password
+fn has_credentials(proxy: &str) -> bool {
+    reqwest::Url::parse(proxy).is_ok_and(|url| !url.username().is_empty() || url.password().is_some())
+}
+
+/// Print `text` as a line to stdout, or append it to `output`
+fn emit(output: Option<&Path>, text: &str) -> anyhow::Result<()> {
+    match output {
+        Some(path) if !crate::output::is_stdout(path) => {
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| anyhow::anyhow!("failed to open {}: {e}", path.display()))?;
+            writeln!(file, "{text}")?;
+        }
+        _ => println!("{text}"),
+    }
+    Ok(())
+}
+
+/// Write `headers` and the `proxy` as a new curl config file, readable by the owner only.
+/// An existing file (or symlink) at `path` is never reused, as someone else may be able to read it;
+/// within one process, the file is written once and shared by all dispatched targets.
+fn write_curl_config(path: &Path, headers: &[(String, String)], proxy: Option<&str>) -> anyhow::Result<()> {
+    static WRITTEN: std::sync::Mutex<Vec<PathBuf>> = std::sync::Mutex::new(Vec::new());
+    let mut written = WRITTEN.lock().unwrap_or_else(|e| e.into_inner());
+    if written.iter().any(|p| p == path) {
+        return Ok(());
+    }
+    let quote = |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut config = headers.iter().map(|(name, value)| format!("header = {}\n", quote(&format!("{name}: {value}")))).collect::<String>();
+    if let Some(proxy) = proxy {
+        config.push_str(&format!("proxy = {}\n", quote(proxy)));
+    }
+    let mut file = fs::OpenOptions::new();
+    // create_new fails on any existing path, symlinks included, instead of following them
+    file.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut file, 0o600);
+    file.open(path)
+        .and_then(|mut file| file.write_all(config.as_bytes()))
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => {
+                anyhow::anyhow!("curl config {} already exists; remove it or pass another --curl-config path", path.display())
+            }
+            _ => anyhow::anyhow!("failed to write curl config {}: {e}", path.display()),
+        })?;
+    written.push(path.to_path_buf());
+    Ok(())
+}
+
+/// Headers of the printed curl command: the defaults, each replaced by an extra header of the same name
+fn curl_headers(token: &str, api_version: &str, extra: &[(String, String)]) -> Vec<(String, String)> {
+    let mut headers = vec![
+        ("Accept".to_string(), "application/vnd.github+json".to_string()),
+        ("Authorization".to_string(), format!("Bearer {token}")),
+        ("X-GitHub-Api-Version".to_string(), api_version.to_string()),
+        ("User-Agent".to_string(), github_api::USER_AGENT.to_string()),
+    ];
+    for (name, value) in extra {
+        match headers.iter_mut().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            Some(header) => *header = (name.clone(), value.clone()),
+            None => headers.push((name.clone(), value.clone())),
+        }
+    }
+    headers
+}
+
+/// Parse a `Name: Value` header argument
+pub fn parse_header(header: &str) -> anyhow::Result<(String, String)> {
+    let Some((name, value)) = header.split_once(':') else {
+        anyhow::bail!("Invalid header format, expected \"Name: Value\": {header}");
+    };
+    let name = reqwest::header::HeaderName::from_bytes(name.trim().as_bytes())
+        .map_err(|_| anyhow::anyhow!("Invalid header name in: {header}"))?;
+    let value = value.trim();
+    reqwest::header::HeaderValue::from_str(value).map_err(|_| anyhow::anyhow!("Invalid header value in: {header}"))?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// Turn an unsuccessful dispatch response into an error, with hints for the usual 404 causes
+fn dispatch_error(status: reqwest::StatusCode, body: &str, repo: &str, workflow: &str) -> github_api::ApiError {
+    if status != reqwest::StatusCode::NOT_FOUND {
+        return github_api::ApiError::from_body(status, body);
+    }
+    github_api::ApiError {
+        status,
+        message: format!(
+            "workflow '{workflow}' not found in {repo}. Likely causes:\n\
+            \x20 - the workflow file name is wrong (check .github/workflows/ on the default branch)\n\
+            \x20 - the workflow has no `workflow_dispatch` trigger\n\
+            \x20 - the workflow is disabled\n\
+            \x20 - the token has no access to {repo}"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dispatch_404_lists_likely_causes() {
+        let err = dispatch_error(reqwest::StatusCode::NOT_FOUND, r#"{"message":"Not Found"}"#, "owner/repo", "ci.yml");
+        let text = err.to_string();
+        assert!(text.contains("workflow 'ci.yml' not found in owner/repo"));
+        assert!(text.contains("`workflow_dispatch` trigger"));
+        assert!(text.contains("disabled"));
+        assert!(!text.contains("Not Found\""));
+    }
+
+    #[test]
+    fn repository_dispatch_payload_merges_json_and_args() {
+        let payload = repository_dispatch_payload(
+            "deploy",
+            Some(r#"{"env": "staging", "nested": {"a": 1}}"#),
+            &["env=production".to_string(), "version=1.2.3".to_string()],
+            true,
+        ).unwrap();
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json, serde_json::json!({
+            "event_type": "deploy",
+            "client_payload": {"env": "production", "nested": {"a": 1}, "version": "1.2.3"}
+        }));
+        assert_eq!(repository_dispatch_url("https://api.github.com", "owner/repo"), "https://api.github.com/repos/owner/repo/dispatches");
+    }
+
+    #[test]
+    fn repository_dispatch_payload_rejects_non_object_json() {
+        let err = repository_dispatch_payload("deploy", Some("[1, 2]"), &[], true).err().unwrap();
+        assert!(err.to_string().contains("must be a JSON object"));
+    }
+
+    #[test]
+    fn summary_exit_code_fails_when_any_ref_failed() {
+        let summary = DispatchSummary {
+            results: vec![
+                ("main".into(), RefOutcome::Dispatched(DispatchOutcome::Printed)),
+                ("v1".into(), RefOutcome::Failed(anyhow::anyhow!("boom").into())),
+                ("v2".into(), RefOutcome::Skipped),
+            ],
+        };
+        assert_eq!(summary.exit_code(), exitcode::SOFTWARE);
+        assert_eq!(
+            summary.render(false),
+            "Dispatch summary: 1 dispatched, 1 failed, 1 skipped\n  main: dispatched\n  v1: failed: boom\n  v2: skipped"
+        );
+
+        let summary = DispatchSummary { results: vec![("main".into(), RefOutcome::Dispatched(DispatchOutcome::Printed))] };
+        assert_eq!(summary.exit_code(), exitcode::OK);
+
+        let not_found = github_api::ApiError { status: reqwest::StatusCode::NOT_FOUND, message: String::new() };
+        let summary = DispatchSummary {
+            results: vec![("main".into(), RefOutcome::Skipped), ("v1".into(), RefOutcome::Failed(not_found.into()))],
+        };
+        assert_eq!(summary.exit_code(), exitcode::UNAVAILABLE);
+    }
+
+    #[test]
+    fn summary_json_includes_runs() {
+        let summary = DispatchSummary {
+            results: vec![
+                ("main".into(), RefOutcome::Dispatched(DispatchOutcome::Printed)),
+                ("v1".into(), RefOutcome::Failed(anyhow::anyhow!("boom").into())),
+            ],
+        };
+        let json = summary.to_json(|r| (r == "main").then(|| (7, "https://example.com/runs/7".to_string())));
+        assert_eq!(json, serde_json::json!([
+            {"ref": "main", "status": "dispatched", "run_id": 7, "run_url": "https://example.com/runs/7"},
+            {"ref": "v1", "status": "failed", "error": "boom"}
+        ]));
+    }
+
+    #[tokio::test]
+    async fn dispatch_refs_aggregates_in_input_order() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({"ref": "broken"})))
+            .respond_with(ResponseTemplate::new(422).set_body_string(r#"{"message":"No ref found for: broken"}"#))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let gh = GithubClient::new(&server.uri(), "t0ken", &github_api::ClientOptions::default()).unwrap();
+        let refs: Vec<String> = ["main", "broken", "v1", "v2", "v3"].iter().map(|r| r.to_string()).collect();
+        let summary = workflow_dispatch_refs(&gh, "owner/repo", "ci.yml", &refs, &[], &DispatchOptions::new("call"), false).await;
+
+        let order: Vec<&str> = summary.results.iter().map(|(r, _)| r.as_str()).collect();
+        assert_eq!(order, ["main", "broken", "v1", "v2", "v3"]);
+        assert!(matches!(summary.results[1].1, RefOutcome::Failed(_)));
+        assert_eq!(summary.results.iter().filter(|(_, o)| matches!(o, RefOutcome::Dispatched(_))).count(), 4);
+        assert_eq!(summary.exit_code(), exitcode::DATAERR);
+    }
+
+    #[tokio::test]
+    async fn dispatch_sends_inputs_in_arg_order() {
+        use wiremock::matchers::{body_string, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string(r#"{"ref":"main","inputs":{"zone":"eu","version":"1.2.3","app":"web"}}"#))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let gh = GithubClient::new(&server.uri(), "t0ken", &github_api::ClientOptions::default()).unwrap();
+        let args: Vec<String> = ["zone=eu", "version=1.2.3", "app=web"].iter().map(|a| a.to_string()).collect();
+        let summary = workflow_dispatch_refs(&gh, "owner/repo", "ci.yml", &["main".to_string()], &args, &DispatchOptions::new("call"), false).await;
+        assert_eq!(summary.exit_code(), exitcode::OK);
+    }
+
+    #[tokio::test]
+    async fn dispatch_refs_fail_fast_stops_printing() {
+        let gh = GithubClient::new("https://api.github.com", "t0ken", &github_api::ClientOptions::default()).unwrap();
+        let refs = vec!["main".to_string(), "v1".to_string()];
+        let summary = workflow_dispatch_refs(&gh, "owner/repo", "ci.yml", &refs, &[], &DispatchOptions::new("bogus"), true).await;
+        assert!(matches!(summary.results[0].1, RefOutcome::Failed(_)));
+        assert!(matches!(summary.results[1].1, RefOutcome::Skipped));
+    }
+
+    #[tokio::test]
+    async fn dispatch_many_aggregates_per_workflow() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/repos/owner/repo/actions/workflows/deploy.yml/dispatches"))
+            .respond_with(ResponseTemplate::new(404).set_body_string(r#"{"message":"Not Found"}"#))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let gh = GithubClient::new(&server.uri(), "t0ken", &github_api::ClientOptions::default()).unwrap();
+        let none = serde_json::Map::new;
+        let targets = vec![("ci.yml".to_string(), none()), ("deploy.yml".to_string(), none()), ("docs.yml".to_string(), none())];
+        let refs = vec!["main".to_string(), "v1".to_string()];
+        let summaries = workflow_dispatch_many(&gh, "owner/repo", &targets, &refs, &DispatchOptions::new("call"), false).await;
+
+        let workflows: Vec<&str> = summaries.iter().map(|(w, _)| w.as_str()).collect();
+        assert_eq!(workflows, ["ci.yml", "deploy.yml", "docs.yml"]);
+        assert_eq!(summaries[0].1.exit_code(), exitcode::OK);
+        assert!(summaries[1].1.results.iter().all(|(_, o)| matches!(o, RefOutcome::Failed(_))));
+        assert_eq!(summaries[2].1.exit_code(), exitcode::OK);
+        assert_eq!(combined_exit_code(&summaries), github_api::status_exit_code(reqwest::StatusCode::NOT_FOUND));
+        assert_eq!(combined_exit_code(&summaries[2..]), exitcode::OK);
+    }
+
+    #[tokio::test]
+    async fn dispatch_many_keeps_at_most_concurrency_requests_in_flight() {
+        use std::sync::Mutex;
+        use std::time::{Duration, Instant};
+        use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+        /// Records when each request arrives; every response takes `DELAY`
+        struct Arrivals(Arc<Mutex<Vec<Instant>>>);
+        impl Respond for Arrivals {
+            fn respond(&self, _: &Request) -> ResponseTemplate {
+                self.0.lock().unwrap().push(Instant::now());
+                ResponseTemplate::new(204).set_delay(DELAY)
+            }
+        }
+        const DELAY: Duration = Duration::from_millis(150);
+
+        let server = MockServer::start().await;
+        let arrivals = Arc::new(Mutex::new(Vec::new()));
+        Mock::given(wiremock::matchers::method("POST")).respond_with(Arrivals(arrivals.clone())).mount(&server).await;
+
+        let gh = GithubClient::new(&server.uri(), "t0ken", &github_api::ClientOptions::default()).unwrap();
+        let targets = vec![("ci.yml".to_string(), serde_json::Map::new()), ("deploy.yml".to_string(), serde_json::Map::new())];
+        let refs: Vec<String> = (1..=4).map(|i| format!("v{i}")).collect();
+        let options = DispatchOptions { concurrency: 2, ..DispatchOptions::new("call") };
+        let summaries = workflow_dispatch_many(&gh, "owner/repo", &targets, &refs, &options, false).await;
+
+        assert!(summaries.iter().all(|(_, summary)| summary.exit_code() == exitcode::OK));
+        let order: Vec<&str> = summaries[1].1.results.iter().map(|(r, _)| r.as_str()).collect();
+        assert_eq!(order, ["v1", "v2", "v3", "v4"]);
+        // a request can only start once one of the two before it has got its delayed response
+        let arrivals = arrivals.lock().unwrap();
+        assert_eq!(arrivals.len(), 8);
+        for (earlier, later) in arrivals.iter().zip(arrivals.iter().skip(2)) {
+            assert!(later.duration_since(*earlier) >= DELAY, "three requests in flight at once");
+        }
+    }
+
+    #[test]
+    fn drops_undeclared_inputs() {
+        let info = crate::gen_client::parse_workflow(Path::new("tests/dispatch_inputs.yml")).unwrap().unwrap();
+        let inputs = parse_args(&args(&["version=1.0", "environment=production", "unknown:=true"])).unwrap();
+        let applicable = applicable_inputs(&inputs, &info);
+        assert_eq!(applicable.keys().collect::<Vec<_>>(), ["version", "environment"]);
+    }
+
+    #[test]
+    fn parses_headers() {
+        assert_eq!(parse_header("X-Trace:  abc ").unwrap(), ("x-trace".to_string(), "abc".to_string()));
+        assert!(parse_header("X-Trace abc").unwrap_err().to_string().contains("expected \"Name: Value\""));
+        assert!(parse_header("Bad Name: abc").is_err());
+    }
+
+    #[test]
+    fn extra_headers_replace_curl_defaults() {
+        let headers = curl_headers("t0ken", "2022-11-28", &[("accept".into(), "application/vnd.github.raw+json".into()), ("x-trace".into(), "1".into())]);
+        assert_eq!(headers[0], ("accept".to_string(), "application/vnd.github.raw+json".to_string()));
+        assert_eq!(headers.len(), 5);
+    }
+
+    #[test]
+    fn warns_about_too_many_inputs() {
+        let args: Vec<String> = (1..=11).map(|i| format!("input{i}=value")).collect();
+        let inputs = parse_args(&args).unwrap();
+        assert_eq!(input_limit_problems(&inputs), ["11 inputs given, but GitHub accepts at most 10"]);
+        assert!(input_limit_problems(&parse_args(&args[..10]).unwrap()).is_empty());
+
+        let huge = parse_args(&[format!("notes={}", "x".repeat(70_000))]).unwrap();
+        assert!(input_limit_problems(&huge)[0].starts_with("inputs are 70012 bytes"));
+    }
+
+    #[tokio::test]
+    async fn strict_rejects_too_many_inputs() {
+        let gh = GithubClient::new("https://api.github.com", "t0ken", &github_api::ClientOptions::default()).unwrap();
+        let args: Vec<String> = (1..=11).map(|i| format!("input{i}=value")).collect();
+        let options = DispatchOptions { strict: true, ..DispatchOptions::new("curl") };
+        let err = workflow_dispatch(&gh, "owner/repo", "ci.yml", "main", &args, &options).await.unwrap_err();
+        assert!(err.to_string().contains("at most 10"));
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_string_file_and_stdin_args() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let file = temp.path().join("notes.txt");
+        fs::write(&file, "from file").unwrap();
+
+        let inputs = parse_args_from(
+            &args(&["name=World", "empty=", "eq=a=b", &format!("notes=@{}", file.display()), "body=@-"]),
+            "from stdin".as_bytes(),
+            true,
+        )
+        .unwrap();
+        assert_eq!(serde_json::Value::Object(inputs), serde_json::json!({
+            "name": "World",
+            "empty": "",
+            "eq": "a=b",
+            "notes": "from file",
+            "body": "from stdin",
+        }));
+    }
+
+    #[test]
+    fn base64_encodes_file_args() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let file = temp.path().join("blob.bin");
+        let bytes = [0u8, 159, 146, 150, 255, b'\n'];
+        fs::write(&file, bytes).unwrap();
+
+        let inputs = parse_args_from(&args(&[&format!("blob=@base64:{}", file.display())]), std::io::empty(), true).unwrap();
+        let encoded = inputs["blob"].as_str().unwrap();
+        assert_eq!(encoded, "AJ+Slv8K");
+        assert_eq!(base64::engine::general_purpose::STANDARD.decode(encoded).unwrap(), bytes);
+    }
+
+    #[test]
+    fn trims_one_trailing_newline_of_file_args() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let file = temp.path().join("version.txt");
+        fs::write(&file, "1.2.3\n").unwrap();
+        let arg = args(&[&format!("version=@{}", file.display())]);
+
+        assert_eq!(parse_args_from(&arg, std::io::empty(), true).unwrap()["version"], "1.2.3");
+        assert_eq!(parse_args_from(&arg, std::io::empty(), false).unwrap()["version"], "1.2.3\n");
+
+        fs::write(&file, "two\r\nlines\n\n").unwrap();
+        assert_eq!(parse_args_from(&arg, std::io::empty(), true).unwrap()["version"], "two\r\nlines\n");
+    }
+
+    #[test]
+    fn reads_inputs_from_an_env_file_with_arg_overrides() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let file = temp.path().join("deploy.env");
+        fs::write(
+            &file,
+            "# deployment\nVERSION=1.2.3\n\nENVIRONMENT=staging # comment\nNotes=\"@not a file\"\n\
+             export COST=cost $5\nPATTERN='a$b # c'\nTEMPLATE=\"${HOME} \\\"quoted\\\"\\n\"\n",
+        )
+        .unwrap();
+
+        let mut lowercased = env_file_args(&file, false).unwrap();
+        lowercased.push("environment=prod".to_string());
+        let inputs = parse_args_from(&lowercased, std::io::empty(), true).unwrap();
+        assert_eq!(serde_json::Value::Object(inputs), serde_json::json!({
+            "version": "1.2.3",
+            "environment": "prod",
+            "notes": "@not a file",
+            "cost": "cost $5",
+            "pattern": "a$b # c",
+            "template": "${HOME} \"quoted\"\n",
+        }));
+
+        let inputs = parse_args_from(&env_file_args(&file, true).unwrap(), std::io::empty(), true).unwrap();
+        assert_eq!(inputs.keys().collect::<Vec<_>>(), ["VERSION", "ENVIRONMENT", "Notes", "COST", "PATTERN", "TEMPLATE"]);
+        assert!(env_file_args(&temp.path().join("missing.env"), false).is_err());
+
+        fs::write(&file, "OK=1\nBROKEN=\"open\n").unwrap();
+        let err = env_file_args(&file, false).unwrap_err().to_string();
+        assert!(err.ends_with("line 2: unterminated double quote"), "{err}");
+    }
+
+    #[test]
+    fn reads_inputs_from_toml_yaml_and_json_files() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let files = [
+            ("inputs.toml", "version = \"1.2.3\"\ndry_run = true\nreplicas = 3\n"),
+            ("inputs.yaml", "version: 1.2.3\ndry_run: true\nreplicas: 3\n"),
+            ("inputs.yml", "version: \"1.2.3\"\ndry_run: true\nreplicas: 3\n"),
+            ("inputs.JSON", r#"{"version": "1.2.3", "dry_run": true, "replicas": 3}"#),
+        ];
+        for (name, content) in files {
+            let file = temp.path().join(name);
+            fs::write(&file, content).unwrap();
+            let inputs = parse_args_from(&input_file_args(&file, false).unwrap(), std::io::empty(), true).unwrap();
+            assert_eq!(serde_json::Value::Object(inputs), serde_json::json!({"version": "1.2.3", "dry_run": "true", "replicas": "3"}), "{name}");
+            let typed = parse_args_from(&input_file_args(&file, true).unwrap(), std::io::empty(), true).unwrap();
+            assert_eq!(serde_json::Value::Object(typed), serde_json::json!({"version": "1.2.3", "dry_run": true, "replicas": 3}), "{name}");
+        }
+    }
+
+    #[test]
+    fn reads_the_ref_and_inputs_of_a_request_body() {
+        let (git_ref, args) = input_json_args(r#"{"ref": "main", "inputs": {"version": "1.2.3", "dry_run": true}}"#).unwrap();
+        assert_eq!(git_ref.as_deref(), Some("main"));
+        let inputs = parse_args_from(&[args, vec!["version=2.0".to_string()]].concat(), std::io::empty(), true).unwrap();
+        assert_eq!(serde_json::Value::Object(inputs), serde_json::json!({"version": "2.0", "dry_run": true}));
+
+        assert_eq!(input_json_args("{}").unwrap(), (None, Vec::new()));
+        assert!(input_json_args(r#"{"version": "1.2.3"}"#).unwrap_err().to_string().contains("unexpected \"version\""));
+        assert!(input_json_args(r#"{"ref": 1}"#).unwrap_err().to_string().contains("\"ref\" in --input-json must be a string"));
+        assert!(input_json_args(r#"{"inputs": {"tags": ["a"]}}"#).unwrap_err().to_string().contains("input 'tags'"));
+    }
+
+    #[test]
+    fn rejects_input_files_that_do_not_map_to_inputs() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let error = |name: &str, content: &str| {
+            let file = temp.path().join(name);
+            fs::write(&file, content).unwrap();
+            input_file_args(&file, false).unwrap_err().to_string()
+        };
+        assert!(error("nested.toml", "[deploy]\nregion = \"eu\"\n").contains("input 'deploy' in "));
+        assert!(error("list.yaml", "tags: [a, b]\n").contains("workflow inputs must be strings, numbers or booleans"));
+        assert!(error("null.json", r#"{"notes": null}"#).contains("input 'notes'"));
+        assert!(error("list.json", "[1, 2]").contains("must contain a table of inputs"));
+        assert!(error("inputs.ini", "a=b").contains("use a .toml, .yaml, .yml or .json file"));
+    }
+
+    #[test]
+    fn parses_typed_args() {
+        let inputs = parse_args_from(&args(&["debug:=true", "count:=3", "tags:=[\"a\", \"b\"]", "plain=true"]), std::io::empty(), true).unwrap();
+        assert_eq!(serde_json::Value::Object(inputs), serde_json::json!({
+            "debug": true,
+            "count": 3,
+            "tags": ["a", "b"],
+            "plain": "true",
+        }));
+
+        let inputs = parse_args_from(&args(&["config:=@-"]), r#"{"a": 1}"#.as_bytes(), true).unwrap();
+        assert_eq!(inputs["config"], serde_json::json!({"a": 1}));
+    }
+
+    #[test]
+    fn rejects_malformed_args() {
+        let err = |a: &[&str]| parse_args_from(&args(a), "x".as_bytes(), true).unwrap_err().to_string();
+        assert_eq!(err(&["no-equals-sign"]), "Invalid arg format: no-equals-sign");
+        assert_eq!(err(&["=value"]), "Invalid arg format, missing input name: =value");
+        assert_eq!(err(&[":=1"]), "Invalid arg format, missing input name: :=1");
+        assert!(err(&["notes=@/does/not/exist"]).starts_with("failed to read /does/not/exist for notes"));
+        assert!(err(&["count:=three"]).starts_with("Invalid JSON value for count"));
+        assert_eq!(err(&["a=@-", "b=@-"]), "Only one --arg can be read from stdin: b=@-");
+    }
+
+    #[test]
+    fn expands_environment_variables_in_inline_values() {
+        let lookup = |name: &str| (name == "VERSION").then(|| "1.2.3".to_string());
+        let expand = |list: &[&str], undefined_empty| expand_env_args(&args(list), lookup, undefined_empty);
+
+        assert_eq!(
+            expand(&["version=v${VERSION}", "notes=@${VERSION}.txt", "flags:=[\"${VERSION}\"]"], false).unwrap(),
+            ["version=v1.2.3", "notes=@${VERSION}.txt", "flags:=[\"1.2.3\"]"]
+        );
+        assert_eq!(expand(&["price=$$5 or $5 for ${VERSION}"], false).unwrap(), ["price=$5 or $5 for 1.2.3"]);
+
+        let err = expand(&["token=${TOKEN}"], false).unwrap_err();
+        assert_eq!(err.to_string(), "Undefined environment variable TOKEN (--expand-env-default expands it to nothing) in --arg token=${TOKEN}");
+        assert_eq!(expand(&["token=[${TOKEN}]"], true).unwrap(), ["token=[]"]);
+        assert!(expand(&["token=${TOKEN"], true).unwrap_err().to_string().starts_with("Unterminated ${"));
+    }
+
+    #[test]
+    fn recognizes_pull_request_refs() {
+        assert_eq!(pr_number("pr/123"), Some(123));
+        assert_eq!(pr_number("#7"), Some(7));
+        assert_eq!(pr_number("pr/main"), None);
+        assert_eq!(pr_number("main"), None);
+    }
+
+    #[test]
+    fn recognizes_full_shas() {
+        assert!(looks_like_sha("0123456789abcdef0123456789abcdef01234567"));
+        assert!(!looks_like_sha("0123456"));
+        assert!(!looks_like_sha("main"));
+    }
+
+    #[test]
+    fn dispatch_other_errors_keep_body() {
+        let err = dispatch_error(reqwest::StatusCode::INTERNAL_SERVER_ERROR, "boom", "owner/repo", "ci.yml");
+        assert_eq!(err.message, "boom");
+    }
+}
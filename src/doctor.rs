@@ -0,0 +1,118 @@
+use crate::output::OutputFormat;
+use serde::Serialize;
+
+/// Resolved settings and environment checks reported by `gha doctor`.
+///
+/// Holds the sources of credentials only, never their values.
+#[derive(Debug, Serialize)]
+pub struct DoctorReport {
+    /// Resolved "owner/repo"
+    pub repo: Option<String>,
+    /// Where the repository came from, e.g. `--repo` or `git remote origin`
+    pub repo_source: String,
+    pub r#ref: Option<String>,
+    pub workflow: Option<String>,
+    pub api_url: String,
+    /// Where the token came from, e.g. `--token` or `GITHUB_TOKEN environment variable`
+    pub token_source: Option<String>,
+    /// `(name, url)` of the git remote the repository is detected from
+    pub git_remote: Option<(String, String)>,
+    /// Workflow files found in `.github/workflows`; `None` when the directory is missing
+    pub workflow_files: Option<usize>,
+    /// HTTP status of the API root, or the error reaching it
+    pub api_status: Result<String, String>,
+}
+
+/// Where `token` came from: the environment variable `var` holding the same value,
+/// a dotenv file among `env_files` defining it, the `GH_TOKEN` workflow token inside GitHub Actions,
+/// or the `--token` flag
+pub fn token_source(token: Option<&str>, var: &str, preset: bool, env_files: &[std::path::PathBuf]) -> Option<String> {
+    let token = token?;
+    if std::env::var(var).ok().as_deref() != Some(token) {
+        let actions_token = crate::actions::ActionsEnv::detect().and_then(|actions| actions.token);
+        if actions_token.as_deref() == Some(token) && std::env::var("GH_TOKEN").ok().as_deref() == Some(token) {
+            return Some("GH_TOKEN (GitHub Actions)".to_string());
+        }
+        return Some("--token".to_string());
+    }
+    if preset {
+        return Some(format!("{var} environment variable"));
+    }
+    let file = env_files.iter().find(|file| {
+        dotenvy::from_path_iter(file)
+            .map(|mut vars| vars.any(|item| item.is_ok_and(|(name, _)| name == var)))
+            .unwrap_or(false)
+    });
+    match file {
+        Some(file) => Some(format!("{var} from {}", file.display())),
+        None => Some(format!("{var} environment variable")),
+    }
+}
+
+impl DoctorReport {
+    /// One `name: value` line per setting, or JSON
+    pub fn render(&self, format: OutputFormat) -> anyhow::Result<String> {
+        if format == OutputFormat::Json {
+            let mut json = serde_json::to_value(self)?;
+            json["api_status"] = match &self.api_status {
+                Ok(status) => serde_json::json!({"reachable": true, "status": status}),
+                Err(e) => serde_json::json!({"reachable": false, "error": e}),
+            };
+            return Ok(serde_json::to_string_pretty(&json)? + "\n");
+        }
+        let or_none = |value: &Option<String>| value.clone().unwrap_or_else(|| "(none)".to_string());
+        let mut lines = vec![
+            format!("Repository:     {} ({})", or_none(&self.repo), self.repo_source),
+            format!("Ref:            {}", or_none(&self.r#ref)),
+            format!("Workflow:       {}", or_none(&self.workflow)),
+            format!("API URL:        {}", self.api_url),
+            format!("Token:          {}", self.token_source.as_deref().unwrap_or("missing; use --token or set GITHUB_TOKEN")),
+        ];
+        lines.push(match &self.git_remote {
+            Some((name, url)) => format!("Git remote:     {name} {url}"),
+            None => "Git remote:     (none)".to_string(),
+        });
+        lines.push(match self.workflow_files {
+            Some(count) => format!("Workflows dir:  .github/workflows ({count} files)"),
+            None => "Workflows dir:  .github/workflows not found".to_string(),
+        });
+        lines.push(match &self.api_status {
+            Ok(status) => format!("API:            reachable ({status})"),
+            Err(e) => format!("API:            unreachable: {e}"),
+        });
+        Ok(lines.join("\n") + "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_sources_not_secrets() {
+        let report = DoctorReport {
+            repo: Some("owner/repo".into()),
+            repo_source: "--repo".into(),
+            r#ref: None,
+            workflow: None,
+            api_url: "https://api.github.com".into(),
+            token_source: Some("--token".into()),
+            git_remote: None,
+            workflow_files: None,
+            api_status: Err("connection refused".into()),
+        };
+        let text = report.render(OutputFormat::Text).unwrap();
+        assert!(text.starts_with("Repository:     owner/repo (--repo)\nRef:            (none)\n"));
+        assert!(text.contains("Token:          --token\n"));
+        assert!(text.ends_with("API:            unreachable: connection refused\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&report.render(OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["api_status"], serde_json::json!({"reachable": false, "error": "connection refused"}));
+    }
+
+    #[test]
+    fn token_from_flag_when_environment_differs() {
+        assert_eq!(token_source(Some("t0ken"), "GHA_TEST_DOCTOR_UNSET", false, &[]).as_deref(), Some("--token"));
+        assert_eq!(token_source(None, "GHA_TEST_DOCTOR_UNSET", false, &[]), None);
+    }
+}
@@ -0,0 +1,87 @@
+use std::path::{Path, PathBuf};
+
+/// `.env` files from `start_dir` upward until `stop_dir` (inclusive) or the root, nearest first
+pub fn find_env_files(start_dir: &Path, stop_dir: Option<&Path>) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut dir = start_dir.to_path_buf();
+
+    loop {
+        let candidate = dir.join(".env");
+        if candidate.is_file() {
+            files.push(candidate);
+        }
+
+        // Stop if we reached the stop dir or root
+        if Some(dir.as_path()) == stop_dir || !dir.pop() {
+            break files;
+        }
+    }
+}
+
+/// Load all `.env` files found by [`find_env_files`] and return the loaded ones.
+///
+/// Loading never overrides a variable that is already set, and files are applied
+/// nearest first: closer files take precedence over farther ones, and the real
+/// environment takes precedence over all of them.
+pub fn load_env_files(start_dir: &Path, stop_dir: Option<&Path>) -> Vec<PathBuf> {
+    find_env_files(start_dir, stop_dir)
+        .into_iter()
+        .filter(|file| match dotenvy::from_path(file) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("Ignoring {}: {e}", file.display());
+                false
+            }
+        })
+        .collect()
+}
+
+/// Load one explicitly given dotenv file, without overriding variables that are already set.
+/// Returns the absolute path of the loaded file, to report where values came from.
+pub fn load_env_file(path: &Path) -> anyhow::Result<PathBuf> {
+    dotenvy::from_path(path)
+        .map_err(|e| anyhow::anyhow!("failed to load env file {}: {e}", path.display()))?;
+    Ok(std::path::absolute(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::TempDir;
+    use std::fs;
+
+    #[test]
+    fn nearer_env_file_takes_precedence() {
+        let temp = TempDir::new().unwrap();
+        let parent = temp.path().join("parent");
+        let child = parent.join("child");
+        fs::create_dir_all(&child).unwrap();
+        fs::write(parent.join(".env"), "GHA_TEST_NESTED_SHARED=parent\nGHA_TEST_NESTED_PARENT_ONLY=parent\n").unwrap();
+        fs::write(child.join(".env"), "GHA_TEST_NESTED_SHARED=child\n").unwrap();
+        // above the stop dir, must not be loaded
+        fs::write(temp.path().join(".env"), "GHA_TEST_NESTED_ABOVE=above\n").unwrap();
+
+        let loaded = load_env_files(&child, Some(&parent));
+
+        assert_eq!(loaded, vec![child.join(".env"), parent.join(".env")]);
+        assert_eq!(std::env::var("GHA_TEST_NESTED_SHARED").unwrap(), "child");
+        assert_eq!(std::env::var("GHA_TEST_NESTED_PARENT_ONLY").unwrap(), "parent");
+        assert!(std::env::var("GHA_TEST_NESTED_ABOVE").is_err());
+    }
+
+    #[test]
+    fn explicit_env_file_returns_loaded_path() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("ci.env");
+        fs::write(&file, "GHA_TEST_EXPLICIT_FILE=loaded\n").unwrap();
+
+        assert_eq!(load_env_file(&file).unwrap(), file);
+        assert_eq!(std::env::var("GHA_TEST_EXPLICIT_FILE").unwrap(), "loaded");
+    }
+
+    #[test]
+    fn missing_explicit_env_file_is_an_error() {
+        let err = load_env_file(Path::new("does/not/exist.env")).unwrap_err();
+        assert!(err.to_string().contains("does/not/exist.env"));
+    }
+}
@@ -1,74 +1,433 @@
-use crate::git_utils;
+use crate::{git_utils, github_api};
 use anyhow::{Context, Result};
+use rayon::prelude::*;
 use std::{
     fs,
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 /// Normalized workflow info
+#[derive(Debug, Serialize)]
 pub struct WorkflowInfo {
     pub file: String,
     pub name: String,
     pub inputs: Vec<InputInfo>,
 }
 
+/// Workflow triggered by `repository_dispatch` events
+#[derive(Debug, Serialize)]
+pub struct RepositoryDispatchInfo {
+    pub file: String,
+    pub name: String,
+    /// Declared `types`; empty when the workflow accepts any event type
+    pub types: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
 pub struct InputInfo {
     pub name: String,
     pub description: Option<String>,
+    /// As declared; see [`InputInfo::is_required`] for whether a value must be given
     pub required: bool,
     pub default: Option<String>,
     pub ui_type: Option<String>,
     pub options: Vec<String>,
 }
 
+impl InputInfo {
+    /// Whether a value must be given for this input under `semantics`
+    pub fn is_required(&self, semantics: RequiredSemantics) -> bool {
+        match semantics {
+            RequiredSemantics::NoDefault => self.required && self.default.is_none(),
+            RequiredSemantics::Strict => self.required,
+        }
+    }
+}
+
+/// When an input counts as required
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RequiredSemantics {
+    /// Declared `required: true` and without a default; GitHub fills in the default of the others
+    #[default]
+    NoDefault,
+    /// Declared `required: true`, as in the GitHub UI, even with a default
+    Strict,
+}
+
+/// Kind of client generated from the workflows
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClientFormat {
+    /// Makefile with one target per workflow (or choice option)
+    #[default]
+    Make,
+    /// POSIX shell script with one function per target
+    Sh,
+    /// Justfile with one recipe per target
+    Just,
+    /// go-task Taskfile.yml with one task per target
+    Task,
+    /// `.http` request file (REST Client / IntelliJ HTTP Client) with one request per workflow
+    Http,
+}
+
+impl ClientFormat {
+    /// Output file used when none is given
+    pub fn default_output(self) -> &'static str {
+        match self {
+            ClientFormat::Make => "workflow_dispatch.Makefile",
+            ClientFormat::Sh => "workflow_dispatch.sh",
+            ClientFormat::Just => "justfile",
+            ClientFormat::Task => "Taskfile.yml",
+            ClientFormat::Http => "workflow_dispatch.http",
+        }
+    }
+
+    /// Handlebars template; `None` for formats rendered directly from the workflows
+    fn template(self, shell: MakeShell) -> Option<&'static str> {
+        match self {
+            ClientFormat::Make if shell != MakeShell::Sh => Some(WINDOWS_MAKEFILE_TEMPLATE),
+            ClientFormat::Make => Some(MAKEFILE_TEMPLATE),
+            ClientFormat::Sh => Some(SHELL_TEMPLATE),
+            ClientFormat::Just => Some(JUSTFILE_TEMPLATE),
+            ClientFormat::Task => Some(TASKFILE_TEMPLATE),
+            ClientFormat::Http => None,
+        }
+    }
+}
+
+/// How workflow file names and choice options turn into target names
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NamingScheme {
+    /// File name as is, choice option lowercased, e.g. "Deploy-staging"
+    #[default]
+    Lower,
+    /// File name and choice option as is, e.g. "Deploy-Staging"
+    Keep,
+    /// Everything lowercased, other characters than letters and digits replaced by "-", e.g. "deploy-staging"
+    Slug,
+}
+
+/// Target naming of the generated clients
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TargetNaming {
+    /// Prepended to every target name
+    pub prefix: String,
+    pub scheme: NamingScheme,
+}
+
+impl TargetNaming {
+    /// Target for `base` (the workflow file name without extension) and the choice `option`.
+    /// Characters not allowed in Make targets become `_`; the result is not checked for uniqueness.
+    fn target(&self, base: &str, option: Option<&str>) -> String {
+        let name = match (self.scheme, option) {
+            (NamingScheme::Slug, None) => slug(base),
+            (NamingScheme::Slug, Some(option)) => format!("{}-{}", slug(base), slug(option)),
+            (NamingScheme::Lower, Some(option)) => format!("{base}-{}", option.to_lowercase()),
+            (NamingScheme::Keep, Some(option)) => format!("{base}-{option}"),
+            (_, None) => base.to_string(),
+        };
+        format!("{}{name}", self.prefix).replace(|c: char| !c.is_ascii_alphanumeric() && !"-_.".contains(c), "_")
+    }
+}
+
+/// Shell running the recipes of the generated Makefile
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MakeShell {
+    /// POSIX shell, with curl and jq, and targets waiting for the runs
+    #[default]
+    Sh,
+    /// Windows cmd.exe, with curl.exe
+    Cmd,
+    /// Windows PowerShell, with Invoke-RestMethod
+    Powershell,
+}
+
+impl MakeShell {
+    /// `SHELL` and `.SHELLFLAGS` for the non-POSIX shells
+    fn render(self) -> Option<RenderShell> {
+        let (program, flags) = match self {
+            MakeShell::Sh => return None,
+            MakeShell::Cmd => ("cmd.exe", "/c"),
+            MakeShell::Powershell => ("powershell.exe", "-NoProfile -NonInteractive -Command"),
+        };
+        Some(RenderShell { program, flags, powershell: self == MakeShell::Powershell })
+    }
+}
+
+/// Options of the generated clients
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GenOptions {
+    pub naming: TargetNaming,
+    /// Shell of the Makefile recipes; other formats ignore it
+    pub shell: MakeShell,
+    /// Which inputs the targets insist on
+    pub required: RequiredSemantics,
+    /// Also generate a target per event type of the `repository_dispatch` workflows (POSIX Makefile only)
+    pub repository_dispatch: bool,
+    /// Which workflow files to generate targets for
+    pub workflows: WorkflowFilter,
+}
+
+/// Workflow files by glob on their file name, e.g. `deploy-*.yml`.
+/// Without `include` patterns, every file not matching an `exclude` pattern is selected.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WorkflowFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl WorkflowFilter {
+    /// Compile the patterns into a matcher of file names
+    fn matcher(&self) -> Result<impl Fn(&str) -> bool> {
+        let include = glob_set(&self.include)?;
+        let exclude = glob_set(&self.exclude)?;
+        let all = self.include.is_empty();
+        Ok(move |file: &str| (all || include.is_match(file)) && !exclude.is_match(file))
+    }
+}
+
+fn glob_set(patterns: &[String]) -> Result<globset::GlobSet> {
+    let mut builder = globset::GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(globset::Glob::new(pattern).with_context(|| format!("invalid workflow glob {pattern:?}"))?);
+    }
+    Ok(builder.build()?)
+}
+
 /// Entry point: parse workflows, then write Makefile
 pub fn generate_makefile(workflows_dir: &Path, output: &Path) -> Result<()> {
+    generate_client(workflows_dir, output, ClientFormat::Make, &GenOptions::default())
+}
+
+/// Parse workflows, then write the client in the given format to `output`, or to stdout when it is `-`
+pub fn generate_client(workflows_dir: &Path, output: &Path, format: ClientFormat, options: &GenOptions) -> Result<()> {
+    let (workflows, mut model) = load_render_model(workflows_dir, options)?;
+    model.shell = options.shell.render();
+
+    // Render via template
+    let content = match format.template(options.shell) {
+        Some(template) => render_with_template(&model, template)?,
+        None => render_http_file(&model, &workflows, options.required),
+    };
+
+    if crate::output::is_stdout(output) {
+        print!("{content}");
+        return Ok(());
+    }
+    crate::output::create_output_file(output)?;
+    fs::write(output, content)
+        .with_context(|| format!("failed to write {}", output.display()))?;
+    #[cfg(unix)]
+    if format == ClientFormat::Sh {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(output, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+/// Parse the workflows in `workflows_dir` and transform them to the rendering model
+fn load_render_model(workflows_dir: &Path, options: &GenOptions) -> Result<(Vec<WorkflowInfo>, RenderModel)> {
     if !workflows_dir.is_dir() {
         anyhow::bail!("{} is not a directory or does not exist", workflows_dir.display());
     }
     let dir = workflows_dir.canonicalize()?;
     tracing::info!("Discovering workflows in {}", dir.display());
-    let workflows = discover_and_parse(&dir)?;
+    let workflows = discover_and_parse(&dir, &options.workflows)?;
+    let events = if options.repository_dispatch { discover_repository_dispatch(&dir, &options.workflows)? } else { Vec::new() };
+    let model = build_render_model(&dir, &workflows, &events, options)?;
+    Ok((workflows, model))
+}
 
-    // Transform to rendering model
-    let model = build_render_model(&dir, &workflows)?;
+/// The targets a generated client would have, with the workflow each one dispatches, as a table or JSON
+pub fn list_targets(workflows_dir: &Path, options: &GenOptions, format: crate::output::OutputFormat) -> Result<String> {
+    let (_, model) = load_render_model(workflows_dir, options)?;
+    let rows = model
+        .workflows
+        .iter()
+        .flat_map(|wf| &wf.targets)
+        .map(|t| vec![t.target.clone(), t.comment_lines.first().cloned().unwrap_or_default()])
+        .chain(model.repository_dispatches.iter().map(|e| {
+            let event_type = e.event_type.as_deref().unwrap_or("$(EVENT_TYPE)");
+            vec![e.target.clone(), format!("{} ({}): repository_dispatch event {event_type}", e.name, e.file)]
+        }))
+        .collect::<Vec<_>>();
+    if format == crate::output::OutputFormat::Json {
+        let json = rows
+            .iter()
+            .map(|row| serde_json::json!({"target": row[0], "description": row[1]}))
+            .collect::<Vec<_>>();
+        return Ok(serde_json::to_string_pretty(&json)? + "\n");
+    }
+    Ok(crate::output::format_table(&["TARGET", "DESCRIPTION"], &rows))
+}
 
-    // Render via template
-    let content = render_with_template(&model)?;
+/// Write an editable `dispatch-<name>.json` request body, `{"ref": ..., "inputs": {...}}`, per workflow in `workflows_dir` selected by `options` to `out_dir`
+pub fn write_sample_payloads(workflows_dir: &Path, out_dir: &Path, options: &GenOptions) -> Result<Vec<PathBuf>> {
+    write_json_per_workflow(workflows_dir, out_dir, options, |name| format!("dispatch-{name}.json"), |wf, model| sample_payload(wf, &model.reference))
+}
 
-    fs::write(output, content)
-        .with_context(|| format!("failed to write {}", output.display()))
+/// Request body with each input set to its declared default, or to a `<type>` placeholder
+fn sample_payload(wf: &WorkflowInfo, reference: &str) -> serde_json::Value {
+    let inputs = wf
+        .inputs
+        .iter()
+        .map(|inp| {
+            let value = inp.default.clone().unwrap_or_else(|| match inp.ui_type.as_deref() {
+                Some("choice") if !inp.options.is_empty() => format!("<choice: {}>", inp.options.join("|")),
+                ui_type => format!("<{}>", ui_type.unwrap_or("string")),
+            });
+            (inp.name.clone(), serde_json::Value::String(value))
+        })
+        .collect::<serde_json::Map<_, _>>();
+    serde_json::json!({"ref": reference, "inputs": inputs})
 }
 
-/// Discover YAML workflows and parse them
-fn discover_and_parse(path: &Path) -> Result<Vec<WorkflowInfo>> {
-    let mut infos = Vec::new();
+/// Write a JSON Schema of the `inputs` object, `<name>.inputs.schema.json`, per workflow in `workflows_dir` selected by `options` to `out_dir`;
+/// the inputs required by the schemas are those the generated targets require under `options.required`
+pub fn write_input_schemas(workflows_dir: &Path, out_dir: &Path, options: &GenOptions) -> Result<Vec<PathBuf>> {
+    write_json_per_workflow(workflows_dir, out_dir, options, |name| format!("{name}.inputs.schema.json"), |wf, _| inputs_schema(wf, options.required))
+}
 
-    if !path.is_dir() {
-        return Ok(infos);
+/// Write `render` of each workflow to `out_dir`, in the file named by `file_name` of the workflow file name without extension
+fn write_json_per_workflow(
+    workflows_dir: &Path,
+    out_dir: &Path,
+    options: &GenOptions,
+    file_name: impl Fn(&str) -> String,
+    render: impl Fn(&WorkflowInfo, &RenderModel) -> serde_json::Value,
+) -> Result<Vec<PathBuf>> {
+    let (workflows, model) = load_render_model(workflows_dir, options)?;
+    fs::create_dir_all(out_dir).with_context(|| format!("failed to create {}", out_dir.display()))?;
+    let mut written = Vec::new();
+    for wf in &workflows {
+        let path = out_dir.join(file_name(wf.file.trim_end_matches(".yml").trim_end_matches(".yaml")));
+        let json = serde_json::to_string_pretty(&render(wf, &model))? + "\n";
+        fs::write(&path, json).with_context(|| format!("failed to write {}", path.display()))?;
+        written.push(path);
     }
+    Ok(written)
+}
 
-    for entry in fs::read_dir(path)? {
-        let entry = entry?;
-        let path = entry.path();
-        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-            if ext == "yml" || ext == "yaml" {
-                if let Some(info) = parse_workflow(&path)? {
-                    infos.push(info);
-                }
+/// JSON Schema of the `inputs` of a dispatch request.
+/// Booleans and numbers may also be given as strings, as GitHub accepts both;
+/// the inputs required under `required` must be given.
+fn inputs_schema(wf: &WorkflowInfo, required: RequiredSemantics) -> serde_json::Value {
+    let properties = wf
+        .inputs
+        .iter()
+        .map(|inp| {
+            let mut property = match inp.ui_type.as_deref() {
+                Some("boolean") => serde_json::json!({"type": ["boolean", "string"], "enum": [true, false, "true", "false"]}),
+                Some("number") => serde_json::json!({"type": ["number", "string"], "pattern": r"^-?[0-9]+(\.[0-9]+)?$"}),
+                Some("choice") if !inp.options.is_empty() => serde_json::json!({"type": "string", "enum": inp.options}),
+                _ => serde_json::json!({"type": "string"}),
+            };
+            if let Some(description) = &inp.description {
+                property["description"] = description.clone().into();
             }
-        }
+            if let Some(default) = &inp.default {
+                property["default"] = default.clone().into();
+            }
+            (inp.name.clone(), property)
+        })
+        .collect::<serde_json::Map<_, _>>();
+    let required = wf
+        .inputs
+        .iter()
+        .filter(|inp| inp.is_required(required))
+        .map(|inp| inp.name.clone())
+        .collect::<Vec<_>>();
+    serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": format!("Inputs of {} ({})", wf.name, wf.file),
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// Discover YAML workflows and parse them in parallel, ordered by file name
+fn discover_and_parse(path: &Path, filter: &WorkflowFilter) -> Result<Vec<WorkflowInfo>> {
+    if !path.is_dir() {
+        return Ok(Vec::new());
     }
+
+    let parsed = filtered_workflow_files(path, filter)?
+        .par_iter()
+        .map(|path| parse_workflow(path))
+        .collect::<Result<Vec<_>>>()?;
+    let mut infos: Vec<WorkflowInfo> = parsed.into_iter().flatten().collect();
+    infos.sort_by(|a, b| a.file.cmp(&b.file));
     tracing::info!("Found {} workflow files in {}", infos.len(), path.display());
 
     Ok(infos)
 }
 
+/// Discover the YAML workflows triggered by `repository_dispatch`, ordered by file name
+fn discover_repository_dispatch(path: &Path, filter: &WorkflowFilter) -> Result<Vec<RepositoryDispatchInfo>> {
+    let mut infos = Vec::new();
+    for path in filtered_workflow_files(path, filter)? {
+        let yaml = fs::read_to_string(&path)?;
+        let file = path.file_name().unwrap().to_string_lossy().to_string();
+        infos.extend(parse_repository_dispatch_yaml(&file, &yaml).with_context(|| format!("failed to parse {}", path.display()))?);
+    }
+    Ok(infos)
+}
+
+/// The `.yml` and `.yaml` files in `dir`, by file name
+pub fn workflow_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if let Some(ext) = path.extension().and_then(|e| e.to_str())
+            && (ext == "yml" || ext == "yaml")
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Like [`workflow_files`], but without the `.yaml` files that have a [`yml_twin`]: `ci.yml` wins over `ci.yaml`
+pub fn distinct_workflow_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = workflow_files(dir)?;
+    paths.retain(|path| match yml_twin(path) {
+        Some(twin) => {
+            tracing::warn!("Ignoring {}: {} has the same name", path.display(), twin.display());
+            false
+        }
+        None => true,
+    });
+    Ok(paths)
+}
+
+/// The [`distinct_workflow_files`] in `dir` selected by `filter`
+fn filtered_workflow_files(dir: &Path, filter: &WorkflowFilter) -> Result<Vec<PathBuf>> {
+    let selected = filter.matcher()?;
+    let mut paths = distinct_workflow_files(dir)?;
+    paths.retain(|path| path.file_name().is_some_and(|name| selected(&name.to_string_lossy())));
+    Ok(paths)
+}
+
+/// The existing `.yml` file with the same name as the `.yaml` file at `path`
+pub fn yml_twin(path: &Path) -> Option<PathBuf> {
+    let twin = path.with_extension("yml");
+    (path.extension()? == "yaml" && twin.is_file()).then_some(twin)
+}
+
 /// Parse a workflow into WorkflowInfo
 pub fn parse_workflow(path: &Path) -> Result<Option<WorkflowInfo>> {
     let yaml = fs::read_to_string(path)?;
-    let value: serde_json::Value = serde_yml::from_str(&yaml)
-        .with_context(|| format!("failed to parse {}", path.display()))?;
+    let file = path.file_name().unwrap().to_string_lossy().to_string();
+    parse_workflow_yaml(&file, &yaml)
+        .with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Parse workflow YAML content; `file` is the workflow file name
+pub fn parse_workflow_yaml(file: &str, yaml: &str) -> Result<Option<WorkflowInfo>> {
+    let value: serde_json::Value = serde_yml::from_str(yaml)?;
 
     let on = value.get("on");
     if on.is_none() {
@@ -79,19 +438,11 @@ pub fn parse_workflow(path: &Path) -> Result<Option<WorkflowInfo>> {
     let workflow_dispatch = on.get("workflow_dispatch");
     let repository_dispatch = on.get("repository_dispatch");
 
-    if let Some(repository_dispatch) = repository_dispatch {
-        if !repository_dispatch.is_null() {
-            let types = repository_dispatch.get("types")
-                .and_then(|t| t.as_array())
-                .map(|v| {
-                    v.iter()
-                        .filter_map(|t| t.as_str().map(|s| s.to_string()))
-                        .collect::<Vec<_>>()
-                })
-                .unwrap_or_default();
-
-            tracing::warn!("Ignoring repository_dispatch workflow: {} with types: {}", path.display(), types.join(","));
-        }
+    if let Some(repository_dispatch) = repository_dispatch
+        && !repository_dispatch.is_null()
+    {
+        let types = repository_dispatch_types(repository_dispatch);
+        tracing::warn!("Ignoring repository_dispatch workflow: {file} with types: {}", types.join(","));
     }
 
     if workflow_dispatch.is_none() {
@@ -101,6 +452,7 @@ pub fn parse_workflow(path: &Path) -> Result<Option<WorkflowInfo>> {
     }
     let workflow_dispatch = workflow_dispatch.unwrap();
 
+    // in declaration order, thanks to serde_json's `preserve_order`
     let mut inputs = Vec::new();
     if let Some(inputs_hash) = workflow_dispatch.get("inputs").and_then(|i| i.as_object()) {
         for (name, v) in inputs_hash {
@@ -117,13 +469,10 @@ pub fn parse_workflow(path: &Path) -> Result<Option<WorkflowInfo>> {
                 })
                 .unwrap_or_default();
 
-            // Logic from original code: required is true only if explicitly true AND no default
-            let is_required = required && default.is_none();
-
             inputs.push(InputInfo {
                 name: name.to_string(),
                 description,
-                required: is_required,
+                required,
                 default,
                 ui_type,
                 options,
@@ -131,12 +480,42 @@ pub fn parse_workflow(path: &Path) -> Result<Option<WorkflowInfo>> {
         }
     }
 
-    let file = path.file_name().unwrap().to_string_lossy().to_string();
+    let file = file.to_string();
     let name = value.get("name").and_then(|s| s.as_str()).map(|s| s.to_string()).unwrap_or_else(|| file.clone());
 
     Ok(Some(WorkflowInfo { file, name, inputs }))
 }
 
+/// Parse workflow YAML content for a `repository_dispatch` trigger; `file` is the workflow file name
+pub fn parse_repository_dispatch_yaml(file: &str, yaml: &str) -> Result<Option<RepositoryDispatchInfo>> {
+    let value: serde_json::Value = serde_yml::from_str(yaml)?;
+    let types = match value.get("on") {
+        Some(serde_json::Value::Object(on)) => match on.get("repository_dispatch") {
+            Some(repository_dispatch) => repository_dispatch_types(repository_dispatch),
+            None => return Ok(None),
+        },
+        // `on: repository_dispatch` and `on: [push, repository_dispatch]` accept any type
+        Some(serde_json::Value::String(event)) if event == "repository_dispatch" => Vec::new(),
+        Some(serde_json::Value::Array(events)) if events.iter().any(|e| e == "repository_dispatch") => Vec::new(),
+        _ => return Ok(None),
+    };
+    let file = file.to_string();
+    let name = value.get("name").and_then(|s| s.as_str()).map(|s| s.to_string()).unwrap_or_else(|| file.clone());
+    Ok(Some(RepositoryDispatchInfo { file, name, types }))
+}
+
+/// The `types` of an `on.repository_dispatch` trigger
+fn repository_dispatch_types(repository_dispatch: &serde_json::Value) -> Vec<String> {
+    repository_dispatch.get("types")
+        .and_then(|t| t.as_array())
+        .map(|v| {
+            v.iter()
+                .filter_map(|t| t.as_str().map(|s| s.to_string()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,11 +524,51 @@ mod tests {
     #[test]
     fn test_parse_workflow_dispatch_and_push() {
         let path = Path::new("tests/empty.yml");
-        let result = parse_workflow(&path).unwrap();
+        let result = parse_workflow(path).unwrap();
         assert!(result.is_some(), "Workflow should be parsed when workflow_dispatch is present");
         let wf = result.unwrap();
         assert_eq!(wf.file, "empty.yml");
     }
+
+    #[test]
+    fn required_input_with_default_is_required_only_under_strict_semantics() {
+        let yaml = "on:\n  workflow_dispatch:\n    inputs:\n      name:\n        required: true\n        default: World\n";
+        let wf = parse_workflow_yaml("hello.yml", yaml).unwrap().unwrap();
+        let input = &wf.inputs[0];
+        assert!(input.required);
+        assert!(!input.is_required(RequiredSemantics::NoDefault));
+        assert!(input.is_required(RequiredSemantics::Strict));
+    }
+
+    #[test]
+    fn prefers_yml_over_yaml_of_the_same_name() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        for name in ["ci.yaml", "ci.yml", "docs.yaml"] {
+            std::fs::write(temp.path().join(name), "on:\n  workflow_dispatch:\n").unwrap();
+        }
+        let files: Vec<String> = discover_and_parse(temp.path(), &WorkflowFilter::default()).unwrap().into_iter().map(|wf| wf.file).collect();
+        assert_eq!(files, ["ci.yml", "docs.yaml"]);
+        assert_eq!(yml_twin(&temp.path().join("ci.yaml")), Some(temp.path().join("ci.yml")));
+        assert_eq!(yml_twin(&temp.path().join("ci.yml")), None);
+        assert_eq!(workflow_files(temp.path()).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn discovers_workflows_in_file_name_order() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let names: Vec<String> = (0..32).map(|i| format!("wf{:02}.{}", (i * 7) % 32, if i % 2 == 0 { "yml" } else { "yaml" })).collect();
+        for name in &names {
+            std::fs::write(temp.path().join(name), "on:\n  workflow_dispatch:\n").unwrap();
+        }
+        std::fs::write(temp.path().join("README.md"), "not a workflow").unwrap();
+
+        let mut expected = names.clone();
+        expected.sort();
+        for _ in 0..3 {
+            let files: Vec<String> = discover_and_parse(temp.path(), &WorkflowFilter::default()).unwrap().into_iter().map(|wf| wf.file).collect();
+            assert_eq!(files, expected);
+        }
+    }
 }
 
 // ... existing code ...
@@ -159,10 +578,25 @@ use serde::Serialize;
 /// Rendering model (for template)
 #[derive(Serialize)]
 struct RenderModel {
+    /// Detected "owner/repo"; only the default of the `REPO` variable the targets use
     repo: String,
+    /// REST API base URL for the host of the git remote
+    api_url: String,
+    /// Detected ref; only the default of the `REF` variable the targets use
     reference: String,
     workflows: Vec<RenderWorkflow>,
+    /// Targets sending `repository_dispatch` events
+    repository_dispatches: Vec<RenderEvent>,
     all_targets: Vec<String>,
+    /// Recipe shell of the Windows Makefile
+    shell: Option<RenderShell>,
+}
+
+#[derive(Serialize)]
+struct RenderShell {
+    program: &'static str,
+    flags: &'static str,
+    powershell: bool,
 }
 
 #[derive(Serialize)]
@@ -175,25 +609,55 @@ struct RenderWorkflow {
 #[derive(Serialize)]
 struct RenderTarget {
     target: String,
+    /// `target` as a shell function name
+    function: String,
     comment_lines: Vec<String>,
     required_vars: Vec<String>,
     inputs: Vec<RenderInput>,
 }
 
+#[derive(Serialize)]
+struct RenderEvent {
+    target: String,
+    name: String,
+    file: String,
+    /// `None` for workflows accepting any event type; the target then takes it from `EVENT_TYPE`
+    event_type: Option<String>,
+}
+
 #[derive(Serialize)]
 struct RenderInput {
     name: String,
     jq_var: String,
     env_var: Option<String>,
     fixed_value: Option<String>,
+    /// Declared default, escaped for a Makefile `?=` assignment; `None` when it does not fit on one line
+    make_default: Option<String>,
+    /// Declared default as a Go template string for the Taskfile; `None` when it does not fit on one line
+    task_default: Option<String>,
 }
 
-/// Build the render model from parsed workflows and git defaults
-fn build_render_model(base_dir: &Path, workflows: &[WorkflowInfo]) -> Result<RenderModel> {
+/// Build the render model from parsed workflows and git defaults.
+/// Targets keep the order of `workflows`; choice-expanded targets follow the option declaration order,
+/// preceded by a base target when the choice declares a default.
+/// The `events` targets follow, one per declared event type.
+/// Target names that collide after `options.naming` get a `-2`, `-3`, ... suffix.
+fn build_render_model(
+    base_dir: &Path,
+    workflows: &[WorkflowInfo],
+    events: &[RepositoryDispatchInfo],
+    options: &GenOptions,
+) -> Result<RenderModel> {
+    let naming = &options.naming;
     // Defaults from git
-    let repo = git_utils::default_repo_from_git(base_dir)
+    let repo_info = git_utils::default_repo_from_git(base_dir);
+    let repo = repo_info
+        .as_ref()
         .map(|r| format!("{}/{}", r.owner, r.repo))
         .unwrap_or_else(|| "<owner>/<repo>".into());
+    let api_url = repo_info
+        .map(|r| r.api_url())
+        .unwrap_or_else(|| github_api::GITHUB_API_URL.into());
 
     let reference = git_utils::default_ref_from_git(base_dir)
         .map(|r| r.to_string())
@@ -201,6 +665,19 @@ fn build_render_model(base_dir: &Path, workflows: &[WorkflowInfo]) -> Result<Ren
 
     let mut render_workflows = Vec::new();
     let mut all_targets = Vec::new();
+    let mut used = std::collections::HashSet::new();
+    let mut unique = |name: String| {
+        let mut candidate = name.clone();
+        let mut n = 1;
+        while !used.insert(candidate.clone()) {
+            n += 1;
+            candidate = format!("{name}-{n}");
+        }
+        if n > 1 {
+            tracing::warn!("Target name {name} is already taken; using {candidate}");
+        }
+        candidate
+    };
 
     for wf in workflows {
         // Join input names for info log
@@ -220,19 +697,31 @@ fn build_render_model(base_dir: &Path, workflows: &[WorkflowInfo]) -> Result<Ren
 
         let mut targets = Vec::new();
 
-        // If the first input is a choice → generate per option
+        // If the first input is a choice → generate per option,
+        // after a base target that falls back to the declared default of the choice
         if let Some(first) = wf.inputs.first() {
             if first.ui_type.as_deref() == Some("choice") && !first.options.is_empty() {
+                match first.default.as_ref() {
+                    Some(default) if first.options.contains(default) => {
+                        targets.push(build_render_target(&unique(naming.target(&base_target, None)), wf, None, options.required));
+                    }
+                    Some(default) => tracing::warn!(
+                        "{}: default {default:?} of {} is none of its options; not generating a base target",
+                        wf.file,
+                        first.name
+                    ),
+                    None => {}
+                }
                 for opt in &first.options {
-                    let tname = format!("{}-{}", base_target, opt.to_lowercase().replace(':', "_"));
-                    targets.push(build_render_target(&tname, wf, Some((&first.name, opt))));
+                    let tname = unique(naming.target(&base_target, Some(opt)));
+                    targets.push(build_render_target(&tname, wf, Some((&first.name, opt)), options.required));
                 }
             } else {
-                targets.push(build_render_target(&base_target, wf, None));
+                targets.push(build_render_target(&unique(naming.target(&base_target, None)), wf, None, options.required));
             }
         } else {
             // Workflow without inputs
-            targets.push(build_render_target(&base_target, wf, None));
+            targets.push(build_render_target(&unique(naming.target(&base_target, None)), wf, None, options.required));
         }
 
         all_targets.extend(targets.iter().map(|t| t.target.clone()));
@@ -243,11 +732,31 @@ fn build_render_model(base_dir: &Path, workflows: &[WorkflowInfo]) -> Result<Ren
         });
     }
 
+    let mut repository_dispatches = Vec::new();
+    for event in events {
+        tracing::info!("repository_dispatch: {}({})", event.file, event.types.join(", "));
+        let base_target = event.file.trim_end_matches(".yml").trim_end_matches(".yaml");
+        let event_types: Vec<Option<&String>> = if event.types.is_empty() { vec![None] } else { event.types.iter().map(Some).collect() };
+        for event_type in event_types {
+            let target = unique(naming.target(base_target, event_type.map(String::as_str)));
+            all_targets.push(target.clone());
+            repository_dispatches.push(RenderEvent {
+                target,
+                name: event.name.clone(),
+                file: event.file.clone(),
+                event_type: event_type.cloned(),
+            });
+        }
+    }
+
     Ok(RenderModel {
         repo,
+        api_url,
         reference,
         workflows: render_workflows,
+        repository_dispatches,
         all_targets,
+        shell: None,
     })
 }
 
@@ -255,6 +764,7 @@ fn build_render_target(
     target: &str,
     wf: &WorkflowInfo,
     choice: Option<(&String, &String)>,
+    required: RequiredSemantics,
 ) -> RenderTarget {
     // Header comment lines
     let mut comment_lines = Vec::new();
@@ -265,7 +775,7 @@ fn build_render_target(
             inp.name.to_uppercase(),
             inp.ui_type.as_deref().unwrap_or("STRING"),
             inp.description.as_deref().unwrap_or(""),
-            if inp.required { " (required)" } else { "" },
+            if inp.is_required(required) { " (required)" } else { "" },
             inp.default
                 .as_ref()
                 .map(|d| format!(" [default: {}]", if d.len() < 256 {
@@ -280,7 +790,7 @@ fn build_render_target(
     // Required variables for checks
     let mut required_vars = Vec::new();
     for inp in &wf.inputs {
-        if inp.required {
+        if inp.is_required(required) {
             required_vars.push(inp.name.to_uppercase());
         }
     }
@@ -289,6 +799,7 @@ fn build_render_target(
 
     RenderTarget {
         target: target.to_string(),
+        function: target.replace(|c: char| !c.is_ascii_alphanumeric(), "_"),
         comment_lines,
         required_vars,
         inputs,
@@ -301,16 +812,18 @@ fn build_render_inputs(inputs: &[InputInfo], choice: Option<(&String, &String)>)
     for (index, inp) in inputs.iter().enumerate() {
         let jq_var = format!("input_{index}");
 
-        if let Some((choice_name, option)) = &choice {
-            if &inp.name == *choice_name {
-                render_inputs.push(RenderInput {
-                    name: inp.name.clone(),
-                    jq_var,
-                    env_var: None,
-                    fixed_value: Some((*option).clone()),
-                });
-                continue;
-            }
+        if let Some((choice_name, option)) = &choice
+            && &inp.name == *choice_name
+        {
+            render_inputs.push(RenderInput {
+                name: inp.name.clone(),
+                jq_var,
+                env_var: None,
+                fixed_value: Some((*option).clone()),
+                make_default: None,
+                task_default: None,
+            });
+            continue;
         }
 
         render_inputs.push(RenderInput {
@@ -318,26 +831,125 @@ fn build_render_inputs(inputs: &[InputInfo], choice: Option<(&String, &String)>)
             jq_var,
             env_var: Some(inp.name.to_uppercase()),
             fixed_value: None,
+            make_default: inp.default.as_deref().and_then(make_default),
+            task_default: inp.default.as_deref().and_then(task_default),
         });
     }
 
     render_inputs
 }
 
+/// `default` as the right-hand side of a Makefile assignment
+fn make_default(default: &str) -> Option<String> {
+    if default.is_empty() || default.contains('\n') {
+        return None;
+    }
+    Some(default.replace('$', "$$").replace('#', "\\#"))
+}
+
+/// `default` as a raw Go template string, inside the single-quoted YAML value of a Taskfile variable
+fn task_default(default: &str) -> Option<String> {
+    if default.is_empty() || default.contains(['\n', '`']) {
+        return None;
+    }
+    Some(format!("`{}`", default.replace('\'', "''")))
+}
+
 /// Handlebars template for the Makefile
 const MAKEFILE_TEMPLATE: &str = include_str!("template.Makefile");
 
+/// Handlebars template for the Makefile with Windows shells
+const WINDOWS_MAKEFILE_TEMPLATE: &str = include_str!("template.windows.Makefile");
+
+/// Handlebars template for the shell script
+const SHELL_TEMPLATE: &str = include_str!("template.sh");
+
+/// Handlebars template for the justfile
+const JUSTFILE_TEMPLATE: &str = include_str!("template.just");
+
+/// Handlebars template for the Taskfile
+const TASKFILE_TEMPLATE: &str = include_str!("template.Taskfile.yml");
+
+/// Helpers available to the client templates
+mod helpers {
+    use handlebars::handlebars_helper;
+
+    handlebars_helper!(upper: |value: str| value.to_uppercase());
+    handlebars_helper!(slug: |value: str| super::slug(value));
+    handlebars_helper!(json: |value: Json| value.to_string());
+}
+
+/// `value` lowercased, with each run of characters other than ASCII letters and digits replaced by `-`
+fn slug(value: &str) -> String {
+    value
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
 /// Render model using the template (Handlebars)
-fn render_with_template(model: &RenderModel) -> Result<String> {
+///
+/// Templates can use the helpers `upper`, `slug` and `json`, e.g. `{{upper name}}`.
+fn render_with_template(model: &RenderModel, template: &str) -> Result<String> {
     let mut handlebars = handlebars::Handlebars::new();
-    // Makefile should not HTML-escape content
+    // Makefile and scripts should not HTML-escape content
     handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars.register_helper("upper", Box::new(helpers::upper));
+    handlebars.register_helper("slug", Box::new(helpers::slug));
+    handlebars.register_helper("json", Box::new(helpers::json));
     let out = handlebars
-        .render_template(MAKEFILE_TEMPLATE, model)
-        .context("failed to render Makefile template")?;
+        .render_template(template, model)
+        .context("failed to render client template")?;
     Ok(out)
 }
 
+/// Render one `POST` request per workflow in the `.http` format.
+/// Inputs become `{{NAME}}` placeholders; `{{GITHUB_TOKEN}}` comes from the editor's environment.
+fn render_http_file(model: &RenderModel, workflows: &[WorkflowInfo], required: RequiredSemantics) -> String {
+    let mut out = String::new();
+    out.push_str("# workflow_dispatch requests generated by gha\n");
+    out.push_str(&format!("@api_url = {}\n", model.api_url));
+    out.push_str(&format!("@repo = {}\n", model.repo));
+    out.push_str(&format!("@ref = {}\n", model.reference));
+
+    for wf in workflows {
+        out.push_str(&format!("\n### {} ({})\n", wf.name, wf.file));
+        for inp in &wf.inputs {
+            out.push_str(&format!(
+                "# {}: {}{}{}\n",
+                inp.name.to_uppercase(),
+                inp.description.as_deref().unwrap_or(""),
+                if inp.is_required(required) { " (required)" } else { "" },
+                inp.default.as_ref().map(|d| format!(" [default: {d}]")).unwrap_or_default(),
+            ));
+        }
+        out.push_str(&format!("POST {{{{api_url}}}}/repos/{{{{repo}}}}/actions/workflows/{}/dispatches\n", wf.file));
+        out.push_str("Authorization: Bearer {{GITHUB_TOKEN}}\n");
+        out.push_str("Accept: application/vnd.github+json\n");
+        out.push_str(&format!("X-GitHub-Api-Version: {}\n", github_api::GITHUB_API_VERSION));
+        out.push_str(&format!("User-Agent: {}\n", github_api::USER_AGENT));
+        out.push_str("Content-Type: application/json\n\n");
+
+        let inputs = wf
+            .inputs
+            .iter()
+            .map(|inp| {
+                let key = serde_json::Value::String(inp.name.clone());
+                format!("    {key}: \"{{{{{}}}}}\"", inp.name.to_uppercase())
+            })
+            .collect::<Vec<_>>()
+            .join(",\n");
+        if inputs.is_empty() {
+            out.push_str("{\n  \"ref\": \"{{ref}}\",\n  \"inputs\": {}\n}\n");
+        } else {
+            out.push_str(&format!("{{\n  \"ref\": \"{{{{ref}}}}\",\n  \"inputs\": {{\n{inputs}\n  }}\n}}\n"));
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod generated_makefile_tests {
     use super::*;
@@ -401,7 +1013,6 @@ jobs:
         generate_makefile(temp.path(), &output).unwrap();
         let rendered = fs::read_to_string(output).unwrap();
 
-        assert!(rendered.contains(r#"test -n "$(REQUIRED_NAME)" # requires: REQUIRED_NAME"#));
         assert!(rendered.contains(r#"jq -n --arg ref "$(REF)""#));
         assert!(rendered.contains(r#"key:"optional_name""#));
         assert!(rendered.contains(r#"key:"defaulted_name""#));
@@ -410,4 +1021,521 @@ jobs:
         assert!(!rendered.contains(r#""defaulted_name":"$(DEFAULTED_NAME)""#));
         assert!(!rendered.contains("++|++"));
     }
+
+    #[test]
+    fn generated_makefile_assigns_input_defaults() {
+        let temp = TempDir::new().unwrap();
+        fs::copy("tests/dispatch_inputs.yml", temp.path().join("deploy.yml")).unwrap();
+        fs::write(
+            temp.path().join("notify.yml"),
+            "on:\n  workflow_dispatch:\n    inputs:\n      channel:\n        default: '#releases'\n      message:\n        description: no default\n",
+        )
+        .unwrap();
+
+        let output = temp.path().join("generated.mk");
+        generate_makefile(temp.path(), &output).unwrap();
+        let rendered = fs::read_to_string(output).unwrap();
+
+        assert!(rendered.contains("async-deploy: DRY_RUN ?= false\n"));
+        assert!(rendered.contains("async-deploy: ENVIRONMENT ?= staging\n"));
+        assert!(rendered.contains("async-notify: CHANNEL ?= \\#releases\n"));
+        assert!(!rendered.contains("VERSION ?="));
+        assert!(!rendered.contains("MESSAGE ?="));
+    }
+
+    #[test]
+    fn generated_makefile_guards_required_inputs_only() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("mixed.yml"),
+            "on:\n  workflow_dispatch:\n    inputs:\n      tag:\n        required: true\n      \
+             optional_name:\n        required: false\n      defaulted_name:\n        required: true\n        default: World\n",
+        )
+        .unwrap();
+
+        let output = temp.path().join("generated.mk");
+        generate_makefile(temp.path(), &output).unwrap();
+        let rendered = fs::read_to_string(&output).unwrap();
+
+        assert!(rendered.contains("async-mixed:\n\t@test -n \"$(TAG)\" || (echo \"TAG is required\" >&2 && exit 1)\n"));
+        assert_eq!(rendered.matches("is required\" >&2").count(), 1);
+
+        let options = GenOptions { required: RequiredSemantics::Strict, ..GenOptions::default() };
+        generate_client(temp.path(), &output, ClientFormat::Make, &options).unwrap();
+        let rendered = fs::read_to_string(output).unwrap();
+        assert!(rendered.contains("\t@test -n \"$(DEFAULTED_NAME)\" || (echo \"DEFAULTED_NAME is required\" >&2 && exit 1)\n"));
+        assert_eq!(rendered.matches("is required\" >&2").count(), 2);
+    }
+
+    #[test]
+    fn templates_can_use_registered_helpers() {
+        let model = RenderModel {
+            repo: "owner/repo".into(),
+            api_url: "https://api.github.com".into(),
+            reference: "main".into(),
+            workflows: vec![RenderWorkflow { name: "Deploy: Prod & Staging".into(), file: "deploy.yml".into(), targets: Vec::new() }],
+            repository_dispatches: Vec::new(),
+            all_targets: vec!["deploy".into()],
+            shell: None,
+        };
+        let template = "{{#each workflows}}{{upper file}} {{slug name}}{{/each}} {{json all_targets}} {{json reference}}";
+        let rendered = render_with_template(&model, template).unwrap();
+        assert_eq!(rendered, r#"DEPLOY.YML deploy-prod-staging ["deploy"] "main""#);
+    }
+
+    #[test]
+    fn generated_makefile_takes_repo_and_ref_from_overridable_variables() {
+        let temp = TempDir::new().unwrap();
+        let repo = git2::Repository::init_opts(temp.path(), git2::RepositoryInitOptions::new().initial_head("develop")).unwrap();
+        repo.remote("origin", "https://github.com/acme/widgets.git").unwrap();
+        let workflows_dir = temp.path().join(".github/workflows");
+        fs::create_dir_all(&workflows_dir).unwrap();
+        fs::write(workflows_dir.join("ci.yml"), "on:\n  workflow_dispatch:\n").unwrap();
+
+        let output = temp.path().join("generated.mk");
+        generate_makefile(&workflows_dir, &output).unwrap();
+        let rendered = fs::read_to_string(&output).unwrap();
+
+        assert!(rendered.contains("\nREPO ?= acme/widgets\nREF ?= develop\n"));
+        assert_eq!(rendered.matches("acme/widgets").count(), 1, "the detected repo is only a default");
+        assert_eq!(rendered.matches("develop").count(), 1, "the detected ref is only a default");
+        assert!(rendered.contains("'https://api.github.com/repos/$(REPO)/actions/workflows/$1/dispatches'"));
+        assert!(rendered.contains(r#"jq -n --arg ref "$(REF)""#));
+    }
+
+    #[test]
+    fn generated_makefile_authenticates_with_overridable_token() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("ci.yml"), "on:\n  workflow_dispatch:\n").unwrap();
+
+        let output = temp.path().join("generated.mk");
+        generate_makefile(temp.path(), &output).unwrap();
+        let rendered = fs::read_to_string(&output).unwrap();
+
+        assert!(rendered.contains("\nTOKEN ?= $(GITHUB_TOKEN)\n"));
+        assert!(rendered.contains(r#"CURL_AUTH = $(if $(TOKEN),-H "Authorization: Bearer $(TOKEN)",--netrc)"#));
+        assert!(rendered.contains("define WORKFLOW_DISPATCH\n\t@test -n \"$(TOKEN)\" || test -f ~/.netrc || (echo \"TOKEN is required"));
+        assert!(!rendered.contains("Bearer $(GITHUB_TOKEN)"));
+    }
+
+    #[test]
+    fn render_model_order_is_reproducible() {
+        let temp = TempDir::new().unwrap();
+        fs::copy("tests/dispatch_inputs.yml", temp.path().join("deploy.yml")).unwrap();
+        fs::write(temp.path().join("build.yaml"), "on:\n  workflow_dispatch:\n").unwrap();
+        fs::write(
+            temp.path().join("release.yml"),
+            "on:\n  workflow_dispatch:\n    inputs:\n      channel:\n        type: choice\n        options: [stable, beta, alpha]\n",
+        )
+        .unwrap();
+
+        let workflows = discover_and_parse(temp.path(), &WorkflowFilter::default()).unwrap();
+        let model = build_render_model(temp.path(), &workflows, &[], &GenOptions::default()).unwrap();
+
+        assert_eq!(model.all_targets, ["build", "deploy", "release-stable", "release-beta", "release-alpha"]);
+    }
+
+    #[test]
+    fn workflow_globs_select_the_workflows_that_get_targets() {
+        let temp = TempDir::new().unwrap();
+        for name in ["deploy-prod.yml", "deploy-staging.yml", "deploy-test.yaml", "release.yml", "lint.yml"] {
+            fs::write(temp.path().join(name), "on:\n  workflow_dispatch:\n").unwrap();
+        }
+        let targets = |include: &[&str], exclude: &[&str]| {
+            let workflows = WorkflowFilter {
+                include: include.iter().map(|g| g.to_string()).collect(),
+                exclude: exclude.iter().map(|g| g.to_string()).collect(),
+            };
+            let (_, model) = load_render_model(temp.path(), &GenOptions { workflows, ..GenOptions::default() }).unwrap();
+            model.all_targets
+        };
+
+        assert_eq!(targets(&[], &[]).len(), 5);
+        assert_eq!(targets(&["deploy-*.yml"], &[]), ["deploy-prod", "deploy-staging"]);
+        assert_eq!(targets(&["deploy-*", "release.yml"], &["*-staging.yml"]), ["deploy-prod", "deploy-test", "release"]);
+        assert_eq!(targets(&[], &["deploy-*"]), ["lint", "release"]);
+
+        let output = temp.path().join("generated.mk");
+        let options = GenOptions { workflows: WorkflowFilter { include: vec!["release.*".into()], ..Default::default() }, ..GenOptions::default() };
+        generate_client(temp.path(), &output, ClientFormat::Make, &options).unwrap();
+        let rendered = fs::read_to_string(&output).unwrap();
+        assert!(rendered.contains("\nrelease: async-release"), "{rendered}");
+        assert!(!rendered.contains("deploy-prod"));
+
+        let invalid = WorkflowFilter { include: vec!["deploy-[".into()], ..Default::default() };
+        let err = discover_and_parse(temp.path(), &invalid).unwrap_err();
+        assert!(err.to_string().contains("invalid workflow glob \"deploy-[\""), "{err}");
+    }
+
+    #[test]
+    fn inputs_keep_their_declaration_order() {
+        let temp = TempDir::new().unwrap();
+        fs::copy("tests/ordered_inputs.yml", temp.path().join("ordered.yml")).unwrap();
+
+        let output = temp.path().join("generated.mk");
+        generate_makefile(temp.path(), &output).unwrap();
+        let rendered = fs::read_to_string(&output).unwrap();
+        let comments: Vec<&str> = rendered.lines().filter(|l| l.starts_with("# - ")).collect();
+        assert_eq!(comments.len(), 4, "{rendered}");
+        for (comment, name) in comments.iter().zip(["ZONE", "VERSION", "APP", "MODE"]) {
+            assert!(comment.starts_with(&format!("# - {name}:")), "{comment}");
+        }
+        let keys: Vec<&str> = rendered.split("key:\"").skip(1).map(|rest| rest.split('"').next().unwrap()).collect();
+        assert_eq!(keys, ["zone", "version", "app", "mode"]);
+
+        let output = temp.path().join("workflow_dispatch.http");
+        generate_client(temp.path(), &output, ClientFormat::Http, &GenOptions::default()).unwrap();
+        let rendered = fs::read_to_string(&output).unwrap();
+        let body = &rendered[rendered.find("{\n").unwrap()..];
+        let payload: serde_json::Value = serde_json::from_str(body).unwrap();
+        let keys: Vec<&String> = payload["inputs"].as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["zone", "version", "app", "mode"]);
+    }
+
+    #[test]
+    fn choice_with_default_also_gets_a_base_target() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("release.yml"),
+            "on:\n  workflow_dispatch:\n    inputs:\n      channel:\n        type: choice\n        options: [stable, beta]\n        default: beta\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("sync.yml"),
+            "on:\n  workflow_dispatch:\n    inputs:\n      mirror:\n        type: choice\n        options: [eu, us]\n        default: asia\n",
+        )
+        .unwrap();
+
+        let workflows = discover_and_parse(temp.path(), &WorkflowFilter::default()).unwrap();
+        let model = build_render_model(temp.path(), &workflows, &[], &GenOptions::default()).unwrap();
+        assert_eq!(model.all_targets, ["release", "release-stable", "release-beta", "sync-eu", "sync-us"]);
+
+        let targets = &model.workflows[0].targets;
+        assert_eq!(targets[0].inputs[0].env_var.as_deref(), Some("CHANNEL"));
+        assert_eq!(targets[0].inputs[0].make_default.as_deref(), Some("beta"));
+        assert_eq!(targets[1].inputs[0].fixed_value.as_deref(), Some("stable"));
+
+        let output = temp.path().join("generated.mk");
+        generate_makefile(temp.path(), &output).unwrap();
+        let rendered = fs::read_to_string(&output).unwrap();
+        assert!(rendered.contains("\nasync-release: CHANNEL ?= beta\n"), "{rendered}");
+        assert!(rendered.contains("\nrelease-stable:"));
+    }
+
+    #[test]
+    fn lists_targets_with_their_workflow() {
+        let temp = TempDir::new().unwrap();
+        fs::copy("tests/dispatch_inputs.yml", temp.path().join("deploy.yml")).unwrap();
+        fs::write(
+            temp.path().join("release.yml"),
+            "name: Release\non:\n  workflow_dispatch:\n    inputs:\n      channel:\n        type: choice\n        options: [stable, beta]\n",
+        )
+        .unwrap();
+
+        let listing = list_targets(temp.path(), &GenOptions::default(), crate::output::OutputFormat::Text).unwrap();
+        assert_eq!(
+            listing,
+            "TARGET          DESCRIPTION\n\
+             deploy          Deploy (deploy.yml)\n\
+             release-stable  Release (release.yml)\n\
+             release-beta    Release (release.yml)\n"
+        );
+        let json = list_targets(temp.path(), &GenOptions::default(), crate::output::OutputFormat::Json).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json[1], serde_json::json!({"target": "release-stable", "description": "Release (release.yml)"}));
+        assert!(!temp.path().join("workflow_dispatch.Makefile").exists());
+    }
+
+    #[test]
+    fn writes_sample_payload_per_workflow() {
+        let temp = TempDir::new().unwrap();
+        fs::copy("tests/dispatch_inputs.yml", temp.path().join("deploy.yml")).unwrap();
+        fs::write(temp.path().join("build.yaml"), "on:\n  workflow_dispatch:\n").unwrap();
+
+        let out_dir = temp.path().join("payloads");
+        let written = write_sample_payloads(temp.path(), &out_dir, &GenOptions::default()).unwrap();
+        assert_eq!(written, [out_dir.join("dispatch-build.json"), out_dir.join("dispatch-deploy.json")]);
+
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&written[1]).unwrap()).unwrap();
+        assert_eq!(json["ref"], "main");
+        assert_eq!(json["inputs"], serde_json::json!({"dry_run": "false", "environment": "staging", "version": "<string>"}));
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&written[0]).unwrap()).unwrap();
+        assert_eq!(json["inputs"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn input_schema_validates_payloads() {
+        let temp = TempDir::new().unwrap();
+        fs::copy("tests/dispatch_inputs.yml", temp.path().join("deploy.yml")).unwrap();
+        fs::write(
+            temp.path().join("scale.yml"),
+            "on:\n  workflow_dispatch:\n    inputs:\n      replicas:\n        type: number\n        required: true\n",
+        )
+        .unwrap();
+
+        let out_dir = temp.path().join("schemas");
+        let written = write_input_schemas(temp.path(), &out_dir, &GenOptions::default()).unwrap();
+        assert_eq!(written, [out_dir.join("deploy.inputs.schema.json"), out_dir.join("scale.inputs.schema.json")]);
+        let validator = |path: &Path| {
+            let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+            jsonschema::validator_for(&schema).unwrap()
+        };
+
+        let deploy = validator(&written[0]);
+        assert!(deploy.is_valid(&serde_json::json!({"version": "1.2.3", "environment": "production", "dry_run": true})));
+        assert!(deploy.is_valid(&serde_json::json!({"version": "1.2.3", "dry_run": "false"})));
+        assert!(!deploy.is_valid(&serde_json::json!({"environment": "staging"})), "version is required");
+        assert!(!deploy.is_valid(&serde_json::json!({"version": "1", "environment": "qa"})), "not an option");
+        assert!(!deploy.is_valid(&serde_json::json!({"version": "1", "dry_run": "yes"})));
+        assert!(!deploy.is_valid(&serde_json::json!({"version": "1", "unknown": "x"})));
+
+        let scale = validator(&written[1]);
+        assert!(scale.is_valid(&serde_json::json!({"replicas": 3})));
+        assert!(scale.is_valid(&serde_json::json!({"replicas": "3"})));
+        assert!(!scale.is_valid(&serde_json::json!({"replicas": "three"})));
+    }
+
+    #[test]
+    fn input_schema_requires_what_the_targets_require() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("deploy.yml"),
+            "on:\n  workflow_dispatch:\n    inputs:\n      version:\n        required: true\n      region:\n        required: true\n        default: eu\n",
+        )
+        .unwrap();
+        let required = |semantics| {
+            let out_dir = temp.path().join(format!("{semantics:?}"));
+            let options = GenOptions { required: semantics, ..GenOptions::default() };
+            let written = write_input_schemas(temp.path(), &out_dir, &options).unwrap();
+            let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(&written[0]).unwrap()).unwrap();
+            schema["required"].clone()
+        };
+
+        assert_eq!(required(RequiredSemantics::NoDefault), serde_json::json!(["version"]));
+        assert_eq!(required(RequiredSemantics::Strict), serde_json::json!(["version", "region"]));
+    }
+
+    #[test]
+    fn target_naming_schemes_produce_valid_unique_targets() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("Deploy.yml"),
+            "on:\n  workflow_dispatch:\n    inputs:\n      env:\n        type: choice\n        options: [EU West, eu/west, \"us:east\"]\n",
+        )
+        .unwrap();
+        let workflows = discover_and_parse(temp.path(), &WorkflowFilter::default()).unwrap();
+        let targets = |prefix: &str, scheme| {
+            let naming = TargetNaming { prefix: prefix.into(), scheme };
+            build_render_model(temp.path(), &workflows, &[], &GenOptions { naming, ..GenOptions::default() }).unwrap().all_targets
+        };
+
+        assert_eq!(targets("", NamingScheme::Lower), ["Deploy-eu_west", "Deploy-eu_west-2", "Deploy-us_east"]);
+        assert_eq!(targets("", NamingScheme::Keep), ["Deploy-EU_West", "Deploy-eu_west", "Deploy-us_east"]);
+        assert_eq!(targets("gh-", NamingScheme::Slug), ["gh-deploy-eu-west", "gh-deploy-eu-west-2", "gh-deploy-us-east"]);
+    }
+
+    #[test]
+    fn generated_shell_script_passes_syntax_check() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("build-image.yml"),
+            r#"name: Build image
+on:
+  workflow_dispatch:
+    inputs:
+      arch:
+        type: choice
+        options: [amd64, arm64]
+      tag:
+        description: Image tag
+        required: true
+jobs:
+  noop:
+    runs-on: ubuntu-latest
+    steps:
+      - run: true
+"#,
+        )
+        .unwrap();
+
+        let output = temp.path().join("client.sh");
+        generate_client(temp.path(), &output, ClientFormat::Sh, &GenOptions::default()).unwrap();
+        let rendered = fs::read_to_string(&output).unwrap();
+
+        assert!(rendered.contains("build_image_amd64() {"));
+        assert!(rendered.contains("build-image-arm64) build_image_arm64 ;;"));
+        assert!(rendered.contains(r#"require TAG "$TAG""#));
+        assert!(rendered.contains(r#"--arg input_0 "arm64""#));
+        assert!(rendered.contains("| workflow_dispatch 'build-image.yml'"));
+        let check = std::process::Command::new("sh").arg("-n").arg(&output).output().unwrap();
+        assert!(check.status.success(), "{}", String::from_utf8_lossy(&check.stderr));
+    }
+
+    #[test]
+    fn generated_powershell_makefile_uses_invoke_rest_method() {
+        let temp = TempDir::new().unwrap();
+        fs::copy("tests/dispatch_inputs.yml", temp.path().join("deploy.yml")).unwrap();
+
+        let output = temp.path().join("windows.mk");
+        let options = GenOptions { shell: MakeShell::Powershell, ..GenOptions::default() };
+        generate_client(temp.path(), &output, ClientFormat::Make, &options).unwrap();
+        let rendered = fs::read_to_string(&output).unwrap();
+
+        assert!(rendered.contains("SHELL := powershell.exe\n"));
+        assert!(rendered.contains("\tInvoke-RestMethod -Method Post -Uri 'https://api.github.com/repos/$(REPO)/actions/workflows/deploy.yml/dispatches'"));
+        assert!(rendered.contains("$(call __REQUIRE__,VERSION)"));
+        assert!(!rendered.contains("jq -n"));
+        assert!(!rendered.contains("\\\n"), "no line continuations");
+
+        // make -n expands the recipes, writing the request body without running PowerShell
+        let Ok(make) = std::process::Command::new("make")
+            .current_dir(temp.path())
+            .args(["-n", "-f", "windows.mk", "deploy", "TOKEN=t0ken", "VERSION=1.0 \"rc\", final", "ENVIRONMENT="])
+            .output()
+        else {
+            return;
+        };
+        assert!(make.status.success(), "{}", String::from_utf8_lossy(&make.stderr));
+        let request: serde_json::Value = serde_json::from_str(&fs::read_to_string(temp.path().join(".gha-init-request.json")).unwrap()).unwrap();
+        assert_eq!(request, serde_json::json!({"ref": "main", "inputs": {"dry_run": "false", "version": "1.0 \"rc\", final"}}));
+    }
+
+    #[test]
+    fn generated_cmd_makefile_uses_curl_exe() {
+        let temp = TempDir::new().unwrap();
+        fs::copy("tests/dispatch_inputs.yml", temp.path().join("deploy.yml")).unwrap();
+
+        let output = temp.path().join("windows.mk");
+        let options = GenOptions { shell: MakeShell::Cmd, ..GenOptions::default() };
+        generate_client(temp.path(), &output, ClientFormat::Make, &options).unwrap();
+        let rendered = fs::read_to_string(&output).unwrap();
+
+        assert!(rendered.contains("SHELL := cmd.exe\n.SHELLFLAGS := /c\n"));
+        assert!(rendered.contains("\tcurl.exe --fail -sSL "));
+        assert!(rendered.contains(r#"-d "@$(__REQUEST__)""#));
+    }
+
+    #[test]
+    fn generated_justfile_has_recipe_per_target() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("release.yml"),
+            r#"name: Release
+on:
+  workflow_dispatch:
+    inputs:
+      channel:
+        type: choice
+        options: [beta, stable]
+jobs:
+  noop:
+    runs-on: ubuntu-latest
+    steps:
+      - run: true
+"#,
+        )
+        .unwrap();
+
+        let output = temp.path().join("justfile");
+        generate_client(temp.path(), &output, ClientFormat::Just, &GenOptions::default()).unwrap();
+        let rendered = fs::read_to_string(&output).unwrap();
+
+        assert!(rendered.contains("\nrelease-beta:\n"));
+        assert!(rendered.contains("\nrelease-stable:\n"));
+        assert!(rendered.contains("{{github_curl}} \"{{api_url}}/repos/$REPO/actions/workflows/release.yml/dispatches\""));
+    }
+
+    #[test]
+    fn generated_taskfile_has_task_per_target_with_required_vars() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("release.yml"),
+            r#"name: Release
+on:
+  workflow_dispatch:
+    inputs:
+      channel:
+        type: choice
+        options: [beta, stable]
+      version:
+        required: true
+      notes:
+        default: "it's done"
+jobs:
+  noop:
+    runs-on: ubuntu-latest
+    steps:
+      - run: true
+"#,
+        )
+        .unwrap();
+
+        let output = temp.path().join("Taskfile.yml");
+        generate_client(temp.path(), &output, ClientFormat::Task, &GenOptions::default()).unwrap();
+        let rendered = fs::read_to_string(&output).unwrap();
+
+        let taskfile: serde_json::Value = serde_yml::from_str(&rendered).unwrap();
+        let names: Vec<&String> = taskfile["tasks"].as_object().unwrap().keys().collect();
+        assert_eq!(names, ["default", "release-beta", "release-stable"]);
+        let beta = &taskfile["tasks"]["release-beta"];
+        assert_eq!(beta["requires"]["vars"], serde_json::json!(["TOKEN", "VERSION"]));
+        assert_eq!(beta["desc"], "Release (release.yml)");
+        assert_eq!(beta["env"]["input_0"], "beta");
+        assert_eq!(beta["env"]["input_1"], r#"{{index . "VERSION"}}"#);
+        assert_eq!(beta["env"]["input_2"], r#"{{index . "NOTES" | default `it's done`}}"#);
+        assert_eq!(taskfile["vars"]["REF"], "{{.REF | default \"main\"}}");
+        assert!(beta["cmds"][0].as_str().unwrap().contains("\"$API_URL/repos/$REPO/actions/workflows/release.yml/dispatches\" -d @-"));
+    }
+
+    #[test]
+    fn generated_makefile_has_target_per_repository_dispatch_type() {
+        let temp = TempDir::new().unwrap();
+        fs::copy("tests/repository_dispatch.yml", temp.path().join("release.yml")).unwrap();
+        fs::copy("tests/empty.yml", temp.path().join("empty.yml")).unwrap();
+        fs::write(temp.path().join("any.yml"), "on: [push, repository_dispatch]\n").unwrap();
+
+        let output = temp.path().join("generated.mk");
+        generate_client(temp.path(), &output, ClientFormat::Make, &GenOptions::default()).unwrap();
+        assert!(!fs::read_to_string(&output).unwrap().contains("/dispatches' -d @-"));
+
+        let options = GenOptions { repository_dispatch: true, ..GenOptions::default() };
+        generate_client(temp.path(), &output, ClientFormat::Make, &options).unwrap();
+        let rendered = fs::read_to_string(&output).unwrap();
+
+        assert!(rendered.contains("\nrelease-deploy:\n"));
+        assert!(rendered.contains("\nrelease-rollback:\n"));
+        assert!(rendered.contains("--arg event_type \"rollback\""));
+        assert!(rendered.contains("\nany:\n\t@test -n \"$(EVENT_TYPE)\""));
+        assert!(rendered.contains(".PHONY: empty any release-deploy release-rollback "));
+
+        let Ok(make) = std::process::Command::new("make")
+            .current_dir(temp.path())
+            .args(["-n", "-f", "generated.mk", "release-deploy", "TOKEN=t0ken", r#"CLIENT_PAYLOAD={"version":"1.0"}"#])
+            .output()
+        else {
+            return;
+        };
+        assert!(make.status.success(), "{}", String::from_utf8_lossy(&make.stderr));
+        let recipe = String::from_utf8_lossy(&make.stdout);
+        assert!(recipe.contains(r#"jq -n --arg event_type "deploy" --argjson client_payload '{"version":"1.0"}'"#), "{recipe}");
+        assert!(recipe.contains("/repos/<owner>/<repo>/dispatches' -d @-"), "{recipe}");
+    }
+
+    #[test]
+    fn generated_http_file_has_request_per_workflow() {
+        let temp = TempDir::new().unwrap();
+        fs::copy("tests/dispatch_inputs.yml", temp.path().join("deploy.yml")).unwrap();
+        fs::copy("tests/empty.yml", temp.path().join("empty.yml")).unwrap();
+
+        let output = temp.path().join("requests.http");
+        generate_client(temp.path(), &output, ClientFormat::Http, &GenOptions::default()).unwrap();
+        let rendered = fs::read_to_string(&output).unwrap();
+
+        assert!(rendered.contains("POST {{api_url}}/repos/{{repo}}/actions/workflows/deploy.yml/dispatches\n"));
+        assert!(rendered.contains("POST {{api_url}}/repos/{{repo}}/actions/workflows/empty.yml/dispatches\n"));
+        let api_version = format!("\nX-GitHub-Api-Version: {}\n", github_api::GITHUB_API_VERSION);
+        assert_eq!(rendered.matches(&api_version).count(), 2);
+        assert_eq!(rendered.matches(&format!("\nUser-Agent: {}\n", github_api::USER_AGENT)).count(), 2);
+        assert!(rendered.contains(r#""version": "{{VERSION}}""#));
+    }
 }
@@ -1,12 +1,27 @@
+use crate::output::OutputFormat;
+use git2::Repository;
+use serde::Serialize;
 use std::fmt::Display;
 use std::path::Path;
-use std::process::Command;
 
 /// Try to get default "owner/repo" from git remote origin
 #[derive(Debug, Clone)]
-pub(crate) struct RepoInfo {
-    pub(crate) owner: String,
-    pub(crate) repo: String,
+pub struct RepoInfo {
+    /// `github.com`, or the host of a GitHub Enterprise Server
+    pub host: String,
+    pub owner: String,
+    pub repo: String,
+}
+
+impl RepoInfo {
+    /// REST API base URL for the repository's host
+    pub fn api_url(&self) -> String {
+        if self.host == "github.com" {
+            crate::github_api::GITHUB_API_URL.to_string()
+        } else {
+            format!("https://{}/api/v3", self.host)
+        }
+    }
 }
 
 impl Display for RepoInfo {
@@ -15,52 +30,83 @@ impl Display for RepoInfo {
     }
 }
 
-pub(crate) fn default_repo_from_git(base_dir: &Path) -> Option<RepoInfo> {
-    let output = Command::new("git")
-        .current_dir(base_dir)
-        .args(["config", "--get", "remote.origin.url"])
-        .output()
-        .ok()?;
+/// Hosts of other forges, whose URLs are never taken for GitHub Enterprise ones
+const NON_GITHUB_HOSTS: [&str; 4] = ["gitlab.com", "bitbucket.org", "codeberg.org", "dev.azure.com"];
 
-    if !output.status.success() {
-        return None;
-    }
+/// Parse "owner/repo" from the GitHub `origin` remote of the checkout in `base_dir`
+pub fn default_repo_from_git(base_dir: &Path) -> Option<RepoInfo> {
+    default_repo_from_remote(base_dir, "origin")
+}
 
-    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+/// Parse "owner/repo" from the GitHub remote named `remote` of the checkout in `base_dir`
+pub fn default_repo_from_remote(base_dir: &Path, remote: &str) -> Option<RepoInfo> {
+    parse_github_url(&remote_url(base_dir, remote)?)
+}
 
-    // Examples:
-    //   https://github.com/owner/repo.git
-    //   git@github.com:owner/repo.git
-    if url.contains("github.com") {
-        if let Some(pos) = url.find("github.com") {
-            let mut path = &url[pos + "github.com".len()..];
+/// URL of the remote named `remote` of the checkout in `base_dir`.
+/// Like git, this finds the checkout from any directory inside it, also in a linked worktree.
+pub fn remote_url(base_dir: &Path, remote: &str) -> Option<String> {
+    let repo = Repository::discover(base_dir).ok()?;
+    let url = repo.config().ok()?.get_string(&format!("remote.{remote}.url")).ok()?;
+    Some(url.trim().to_string()).filter(|url| !url.is_empty())
+}
 
-            // strip leading ':' or '/'
-            if path.starts_with(':') || path.starts_with('/') {
-                path = &path[1..];
-            }
+/// Why no repository is detected from the remote named `remote` of `base_dir`, with what to do instead.
+/// Git itself is not needed, so this also works where it is not installed.
+pub fn explain_missing_repo(base_dir: &Path, remote: &str) -> String {
+    if Repository::discover(base_dir).is_err() {
+        return format!("{} is not inside a git checkout; pass --repo owner/repo", base_dir.display());
+    }
+    match remote_url(base_dir, remote) {
+        None => format!("the git checkout has no remote '{remote}'; pass --repo owner/repo or --git-remote <name>"),
+        Some(url) => format!("git remote '{remote}' ({url}) is not a GitHub URL; pass --repo owner/repo"),
+    }
+}
 
-            // strip trailing ".git"
-            let path = path.strip_suffix(".git").unwrap_or(path);
+/// Why no ref is detected from the checkout in `base_dir`, with what to do instead
+pub fn explain_missing_ref(base_dir: &Path) -> String {
+    match Repository::discover(base_dir) {
+        Err(_) => format!("{} is not inside a git checkout; pass --ref", base_dir.display()),
+        Ok(_) => "HEAD of the git checkout is neither a branch nor a commit; pass --ref".to_string(),
+    }
+}
 
-            // split into owner/repo
-            let mut parts = path.splitn(2, '/');
-            let owner = parts.next()?.to_string();
-            let repo = parts.next()?.to_string();
+/// Parse host and "owner/repo" from a GitHub clone or browser URL, e.g.
+/// `https://github.com/owner/repo.git`, `git@github.com:owner/repo.git`,
+/// `https://github.com/owner/repo/actions` or `ssh://git@ghe.example.com:2222/owner/repo.git`
+pub fn parse_github_url(url: &str) -> Option<RepoInfo> {
+    let (authority, path) = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        // scp-like `git@host:owner/repo`
+        None => url.split_once(':').filter(|(authority, _)| !authority.contains('/'))?,
+    };
+    // drop the user and the port
+    let host = authority.rsplit('@').next()?.split(':').next()?.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host).to_string();
+    if host.is_empty() || NON_GITHUB_HOSTS.contains(&host.as_str()) {
+        return None;
+    }
 
-            return Some(RepoInfo { owner, repo });
-        }
+    // owner/repo, ignoring anything below it such as "/actions" or "/tree/main"
+    let mut parts = path.trim_start_matches('/').split('/');
+    let owner = parts.next().filter(|o| !o.is_empty())?.to_string();
+    let repo = parts.next()?;
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+    if repo.is_empty() {
+        return None;
     }
 
-    None
+    Some(RepoInfo { host, owner, repo: repo.to_string() })
 }
 
+/// Branch name or commit SHA of a checkout
 #[derive(Debug, Clone)]
 pub struct RefInfo {
     r#ref: String,
 }
 
 impl RefInfo {
+    /// Wrap a branch name or SHA
     pub fn new(r#ref: String) -> Self {
         Self { r#ref }
     }
@@ -72,34 +118,242 @@ impl Display for RefInfo {
     }
 }
 
+/// Current branch of the checkout in `base_dir`; when detached, a tag pointing at HEAD or else the HEAD SHA
 pub fn default_ref_from_git(base_dir: &Path) -> Option<RefInfo> {
-    // Try to get branch name
-    let output = Command::new("git")
-        .current_dir(base_dir)
-        .args(["symbolic-ref", "--short", "HEAD"])
-        .output()
-        .ok()?;
-
-    if output.status.success() {
-        let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if !branch.is_empty() {
-            return Some(RefInfo::new(branch));
+    let repo = Repository::discover(base_dir).ok()?;
+    let head = match repo.head() {
+        Ok(head) => head,
+        // a new repository is on its branch before the first commit
+        Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {
+            let head = repo.find_reference("HEAD").ok()?;
+            let branch = head.symbolic_target().ok()??.strip_prefix("refs/heads/")?;
+            return Some(RefInfo::new(branch.to_string()));
         }
+        Err(_) => return None,
+    };
+
+    if head.is_branch() {
+        return head.shorthand().ok().map(|branch| RefInfo::new(branch.to_string()));
+    }
+
+    let sha = head.peel_to_commit().ok()?.id();
+    let tags = repo.tag_names(None).ok()?;
+    let tag = tags.iter().filter_map(|name| name.ok().flatten()).find(|name| {
+        repo.revparse_single(&format!("refs/tags/{name}"))
+            .and_then(|tag| tag.peel_to_commit())
+            .is_ok_and(|commit| commit.id() == sha)
+    });
+    Some(RefInfo::new(tag.map_or_else(|| sha.to_string(), str::to_string)))
+}
+
+/// Like [`default_ref_from_git`], but a detached HEAD resolves to the default branch of `remote`
+/// where that is known from `refs/remotes/{remote}/HEAD`
+pub fn default_ref_or_remote_head(base_dir: &Path, remote: &str) -> Option<RefInfo> {
+    if let Ok(repo) = Repository::discover(base_dir)
+        && repo.head_detached().unwrap_or(false)
+        && let Some(branch) = remote_default_branch(&repo, remote)
+    {
+        return Some(RefInfo::new(branch));
     }
+    default_ref_from_git(base_dir)
+}
 
-    // If not on a branch (detached HEAD), fall back to commit SHA
-    let output = Command::new("git")
-        .current_dir(base_dir)
-        .args(["rev-parse", "HEAD"])
-        .output()
-        .ok()?;
+/// Branch that `refs/remotes/{remote}/HEAD` points to, as set by `git clone` or `git remote set-head`
+fn remote_default_branch(repo: &Repository, remote: &str) -> Option<String> {
+    let head = repo.find_reference(&format!("refs/remotes/{remote}/HEAD")).ok()?;
+    let target = head.symbolic_target().ok()??;
+    target.strip_prefix(&format!("refs/remotes/{remote}/")).map(str::to_string)
+}
+
+/// What the commands would detect from the checkout in `base_dir`, reported by `gha repo`
+#[derive(Debug, Serialize)]
+pub struct Detection {
+    /// "owner/repo" from the remote
+    pub repo: Option<String>,
+    pub host: Option<String>,
+    pub r#ref: Option<String>,
+    /// Name of the remote the repository is detected from
+    pub remote: String,
+    pub remote_url: Option<String>,
+    /// Why `repo` or `ref` is not detected
+    pub problems: Vec<String>,
+}
+
+impl Detection {
+    /// Detect the repository from `remote` and the ref from HEAD, as a dispatch without `--repo` and `--ref` does
+    pub fn detect(base_dir: &Path, remote: &str) -> Self {
+        let repo = default_repo_from_remote(base_dir, remote);
+        let r#ref = default_ref_from_git(base_dir);
+        let mut problems = Vec::new();
+        if repo.is_none() {
+            problems.push(explain_missing_repo(base_dir, remote));
+        }
+        if r#ref.is_none() {
+            problems.push(explain_missing_ref(base_dir));
+        }
+        Self {
+            host: repo.as_ref().map(|r| r.host.clone()),
+            repo: repo.map(|r| r.to_string()),
+            r#ref: r#ref.map(|r| r.to_string()),
+            remote: remote.to_string(),
+            remote_url: remote_url(base_dir, remote),
+            problems,
+        }
+    }
 
-    if output.status.success() {
-        let sha = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if !sha.is_empty() {
-            return Some(RefInfo::new(sha));
+    /// One `name: value` line per setting, or JSON
+    pub fn render(&self, format: OutputFormat) -> anyhow::Result<String> {
+        if format == OutputFormat::Json {
+            return Ok(serde_json::to_string_pretty(self)? + "\n");
         }
+        let or_none = |value: &Option<String>| value.clone().unwrap_or_else(|| "(none)".to_string());
+        let mut lines = vec![
+            format!("Repository:  {}", or_none(&self.repo)),
+            format!("Ref:         {}", or_none(&self.r#ref)),
+            format!("Remote:      {} {}", self.remote, or_none(&self.remote_url)),
+        ];
+        lines.extend(self.problems.iter().map(|problem| format!("Problem:     {problem}")));
+        Ok(lines.join("\n") + "\n")
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_github_urls() {
+        let parse = |url| parse_github_url(url).map(|info| info.to_string());
+        assert_eq!(parse("https://github.com/owner/repo.git").as_deref(), Some("owner/repo"));
+        assert_eq!(parse("git@github.com:owner/repo.git").as_deref(), Some("owner/repo"));
+        assert_eq!(parse("https://github.com/owner/repo/actions/workflows").as_deref(), Some("owner/repo"));
+        assert_eq!(parse("https://github.com/owner").as_deref(), None);
+        assert_eq!(parse("https://gitlab.com/owner/repo").as_deref(), None);
+    }
+
+    /// Repository in `dir` with one commit on `main`
+    fn repo_with_commit(dir: &Path) -> (Repository, git2::Oid) {
+        let repo = Repository::init_opts(dir, git2::RepositoryInitOptions::new().initial_head("main")).unwrap();
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+        let oid = repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[]).unwrap();
+        drop(tree);
+        (repo, oid)
+    }
+
+    #[test]
+    fn resolves_the_current_branch() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let (repo, oid) = repo_with_commit(temp.path());
+        repo.branch("feature/x", &repo.find_commit(oid).unwrap(), false).unwrap();
+        repo.set_head("refs/heads/feature/x").unwrap();
+
+        // also from a subdirectory
+        std::fs::create_dir(temp.path().join("sub")).unwrap();
+        assert_eq!(default_ref_from_git(&temp.path().join("sub")).unwrap().to_string(), "feature/x");
+    }
+
+    #[test]
+    fn resolves_the_branch_before_the_first_commit() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        Repository::init_opts(temp.path(), git2::RepositoryInitOptions::new().initial_head("trunk")).unwrap();
+        assert_eq!(default_ref_from_git(temp.path()).unwrap().to_string(), "trunk");
+    }
+
+    #[test]
+    fn resolves_detached_head_to_tag_or_sha() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let (repo, oid) = repo_with_commit(temp.path());
+        repo.set_head_detached(oid).unwrap();
+        assert_eq!(default_ref_from_git(temp.path()).unwrap().to_string(), oid.to_string());
+
+        repo.tag_lightweight("v1.0", &repo.find_object(oid, None).unwrap(), false).unwrap();
+        assert_eq!(default_ref_from_git(temp.path()).unwrap().to_string(), "v1.0");
+    }
+
+    #[test]
+    fn detached_head_prefers_the_remote_default_branch() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let (repo, oid) = repo_with_commit(temp.path());
+        assert_eq!(default_ref_or_remote_head(temp.path(), "origin").unwrap().to_string(), "main", "on a branch");
+
+        repo.set_head_detached(oid).unwrap();
+        assert_eq!(default_ref_or_remote_head(temp.path(), "origin").unwrap().to_string(), oid.to_string(), "no origin/HEAD");
 
-    None
+        repo.reference("refs/remotes/origin/develop", oid, false, "fetch").unwrap();
+        repo.reference_symbolic("refs/remotes/origin/HEAD", "refs/remotes/origin/develop", false, "set-head").unwrap();
+        assert_eq!(default_ref_or_remote_head(temp.path(), "origin").unwrap().to_string(), "develop");
+        assert_eq!(default_ref_from_git(temp.path()).unwrap().to_string(), oid.to_string());
+    }
+
+    #[test]
+    fn reads_the_remote_from_the_config() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        assert!(default_repo_from_git(temp.path()).is_none(), "not a repository");
+
+        let (repo, _) = repo_with_commit(temp.path());
+        assert!(default_repo_from_git(temp.path()).is_none(), "no remote");
+
+        repo.remote("upstream", "git@ghe.example.com:owner/repo.git").unwrap();
+        assert!(default_repo_from_git(temp.path()).is_none(), "no origin");
+        let info = default_repo_from_remote(temp.path(), "upstream").unwrap();
+        assert_eq!((info.host.as_str(), info.to_string().as_str()), ("ghe.example.com", "owner/repo"));
+    }
+
+    #[test]
+    fn detects_from_nested_directories_and_linked_worktrees() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let main = temp.path().join("main");
+        let (repo, _) = repo_with_commit(&main);
+        repo.remote("origin", "https://github.com/owner/repo.git").unwrap();
+        let nested = main.join(".github/workflows");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(default_repo_from_git(&nested).unwrap().to_string(), "owner/repo");
+        assert_eq!(default_ref_from_git(&nested).unwrap().to_string(), "main");
+
+        // a linked worktree has its own HEAD but shares the remotes
+        let linked = temp.path().join("linked");
+        repo.worktree("hotfix", &linked, None).unwrap();
+        std::fs::create_dir(linked.join("src")).unwrap();
+        assert_eq!(default_repo_from_git(&linked.join("src")).unwrap().to_string(), "owner/repo");
+        assert_eq!(default_ref_from_git(&linked.join("src")).unwrap().to_string(), "hotfix");
+    }
+
+    #[test]
+    fn explains_why_no_repo_is_detected() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        assert!(explain_missing_repo(temp.path(), "origin").ends_with("is not inside a git checkout; pass --repo owner/repo"));
+        assert!(explain_missing_ref(temp.path()).ends_with("is not inside a git checkout; pass --ref"));
+
+        let (repo, _) = repo_with_commit(temp.path());
+        assert_eq!(
+            explain_missing_repo(temp.path(), "origin"),
+            "the git checkout has no remote 'origin'; pass --repo owner/repo or --git-remote <name>"
+        );
+        repo.remote("origin", "https://gitlab.com/owner/repo.git").unwrap();
+        assert_eq!(
+            explain_missing_repo(temp.path(), "origin"),
+            "git remote 'origin' (https://gitlab.com/owner/repo.git) is not a GitHub URL; pass --repo owner/repo"
+        );
+    }
+
+    #[test]
+    fn keeps_the_host_for_the_api_url() {
+        let info = parse_github_url("git@github.com:owner/repo.git").unwrap();
+        assert_eq!(info.host, "github.com");
+        assert_eq!(info.api_url(), "https://api.github.com");
+
+        let info = parse_github_url("https://www.github.com/owner/repo").unwrap();
+        assert_eq!(info.api_url(), "https://api.github.com");
+
+        for url in [
+            "https://ghe.example.com/owner/repo.git",
+            "git@ghe.example.com:owner/repo.git",
+            "ssh://git@GHE.example.com:2222/owner/repo.git",
+        ] {
+            let info = parse_github_url(url).unwrap();
+            assert_eq!((info.host.as_str(), info.to_string().as_str()), ("ghe.example.com", "owner/repo"), "{url}");
+            assert_eq!(info.api_url(), "https://ghe.example.com/api/v3");
+        }
+    }
 }
@@ -1,25 +1,18 @@
-use std::fs;
 use std::path::Path;
 
+/// The workflow file name when `.github/workflows` under `base_dir` holds exactly one;
+/// `ci.yaml` next to `ci.yml` does not count, see [`crate::gen_client::distinct_workflow_files`]
 pub fn default_workflow_from_dir(base_dir: &Path) -> Option<String> {
     let workflows_dir = base_dir.join(".github/workflows");
     if !workflows_dir.exists() {
         return None;
     }
 
-    let mut workflow_files = vec![];
-    if let Ok(entries) = fs::read_dir(workflows_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                if ext == "yml" || ext == "yaml" {
-                    if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                        workflow_files.push(file_name.to_string());
-                    }
-                }
-            }
-        }
-    }
+    let workflow_files: Vec<String> = crate::gen_client::distinct_workflow_files(&workflows_dir)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|path| path.file_name().and_then(|n| n.to_str()).map(str::to_string))
+        .collect();
 
     match workflow_files.len() {
         0 => None,
@@ -34,3 +27,62 @@ pub fn default_workflow_from_dir(base_dir: &Path) -> Option<String> {
     }
 }
 
+
+/// Split the `owner/repo@ref` shorthand into the repository and the ref, if any.
+/// The `@` of an SSH URL like `git@github.com:owner/repo` is not a ref separator; refs cannot contain `:`.
+pub fn split_repo_ref(repo: &str) -> (&str, Option<&str>) {
+    match repo.rsplit_once('@') {
+        Some((_, r#ref)) if r#ref.contains(':') => (repo, None),
+        Some((repo, r#ref)) if !r#ref.is_empty() => (repo, Some(r#ref)),
+        Some((repo, _)) => (repo, None),
+        None => (repo, None),
+    }
+}
+
+/// `owner/repo` from a `--repo` value, which may also be a GitHub browser or clone URL
+pub fn normalize_repo(repo: &str) -> anyhow::Result<String> {
+    if !repo.contains("://") && !repo.starts_with("git@") {
+        return Ok(repo.to_string());
+    }
+    match crate::git_utils::parse_github_url(repo) {
+        Some(info) => Ok(info.to_string()),
+        None => anyhow::bail!("Not a GitHub repository URL: {repo}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_the_workflow_when_yaml_duplicates_yml() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let dir = temp.path().join(".github/workflows");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("ci.yaml"), "on: push\n").unwrap();
+        std::fs::write(dir.join("ci.yml"), "on: push\n").unwrap();
+        assert_eq!(default_workflow_from_dir(temp.path()).as_deref(), Some("ci.yml"));
+
+        std::fs::write(dir.join("docs.yaml"), "on: push\n").unwrap();
+        assert_eq!(default_workflow_from_dir(temp.path()), None);
+    }
+
+    #[test]
+    fn splits_repo_ref_shorthand() {
+        assert_eq!(split_repo_ref("owner/repo@main"), ("owner/repo", Some("main")));
+        assert_eq!(split_repo_ref("owner/repo@release/v1.2"), ("owner/repo", Some("release/v1.2")));
+        assert_eq!(split_repo_ref("owner/repo"), ("owner/repo", None));
+        assert_eq!(split_repo_ref("owner/repo@"), ("owner/repo", None));
+        assert_eq!(split_repo_ref("git@github.com:owner/repo.git"), ("git@github.com:owner/repo.git", None));
+        assert_eq!(split_repo_ref("git@github.com:owner/repo.git@v1"), ("git@github.com:owner/repo.git", Some("v1")));
+    }
+
+    #[test]
+    fn normalizes_repo_urls() {
+        assert_eq!(normalize_repo("https://github.com/owner/repo").unwrap(), "owner/repo");
+        assert_eq!(normalize_repo("git@github.com:owner/repo.git").unwrap(), "owner/repo");
+        assert_eq!(normalize_repo("owner/repo").unwrap(), "owner/repo");
+        assert_eq!(normalize_repo("https://ghe.example.com/owner/repo").unwrap(), "owner/repo");
+        assert!(normalize_repo("https://gitlab.com/owner/repo").unwrap_err().to_string().contains("Not a GitHub repository URL"));
+    }
+}
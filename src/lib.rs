@@ -0,0 +1,40 @@
+//! Command line helpers for GitHub Actions: dispatch workflows, inspect runs
+//! and generate Makefile clients for `workflow_dispatch` workflows.
+//!
+//! The `gha` binary is a thin CLI over this library.
+
+/// Integration with the GitHub Actions runner when running as a workflow step
+pub mod actions;
+/// Token verification against the `/user` endpoint
+pub mod auth;
+/// On-disk cache of API responses, revalidated with ETags
+pub mod cache;
+/// Defaults from user and repository config files
+pub mod config;
+/// Workflow and repository dispatch requests
+pub mod dispatch;
+/// Report of the resolved settings, for `gha doctor`
+pub mod doctor;
+/// Discovery and loading of `.env` files
+pub mod env_files;
+/// Makefile client generation from workflow definitions
+pub mod gen_client;
+/// Repository defaults detected from the local git checkout
+pub mod git_utils;
+/// GitHub REST API plumbing and error handling
+pub mod github_api;
+/// Helpers around the local `.github` directory
+pub mod github_utils;
+/// Output formatting shared by commands
+pub mod output;
+/// API rate-limit quotas
+pub mod rate_limit;
+/// Workflow runs: listing, watching, cancelling and re-running
+pub mod runs;
+/// Lint checks for dispatchable workflow files
+pub mod validate;
+/// Workflows of a repository and their dispatch inputs
+pub mod workflows;
+
+pub use dispatch::{parse_args, parse_input_json, repository_dispatch, workflow_dispatch, DispatchError, DispatchOutcome};
+pub use gen_client::generate_makefile;
@@ -0,0 +1,158 @@
+/// Output format for commands printing API data
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human readable table
+    #[default]
+    Text,
+    /// JSON for machine consumption
+    Json,
+}
+
+/// Meaning of a status word, which decides its color
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tone {
+    Success,
+    Failure,
+    Warning,
+}
+
+/// Whether to color a stream: not with `--no-color` or a non-empty `NO_COLOR`, nor when it is no terminal
+pub fn use_color(no_color: bool, is_terminal: bool) -> bool {
+    color_allowed(no_color, std::env::var_os("NO_COLOR"), is_terminal)
+}
+
+fn color_allowed(no_color: bool, no_color_env: Option<std::ffi::OsString>, is_terminal: bool) -> bool {
+    !no_color && no_color_env.is_none_or(|v| v.is_empty()) && is_terminal
+}
+
+/// `text` in the color of `tone`, or unchanged without `color`
+pub fn paint(text: &str, tone: Tone, color: bool) -> String {
+    use owo_colors::OwoColorize;
+
+    if !color {
+        return text.to_string();
+    }
+    match tone {
+        Tone::Success => text.green().to_string(),
+        Tone::Failure => text.red().to_string(),
+        Tone::Warning => text.yellow().to_string(),
+    }
+}
+
+/// The running [`Spinner`], which log lines are printed above
+static ACTIVE_SPINNER: std::sync::Mutex<Option<indicatif::ProgressBar>> = std::sync::Mutex::new(None);
+
+/// Whether to show a [`Spinner`]: only when both stdout and stderr are terminals, and neither with `--quiet`
+/// nor without colors, so that logs and redirected output stay free of escape codes
+pub fn spinner_enabled(quiet: bool, no_color: bool) -> bool {
+    use std::io::IsTerminal;
+
+    !quiet && use_color(no_color, std::io::stdout().is_terminal()) && std::io::stderr().is_terminal()
+}
+
+/// Spinner with the elapsed time on stderr, cleared when dropped; a no-op unless enabled
+pub struct Spinner(Option<indicatif::ProgressBar>);
+
+impl Spinner {
+    pub fn start(message: &str, enabled: bool) -> Self {
+        if !enabled {
+            return Self(None);
+        }
+        let style = indicatif::ProgressStyle::with_template("{spinner} {msg} ({elapsed})").expect("valid spinner template");
+        let bar = indicatif::ProgressBar::new_spinner().with_style(style).with_message(message.to_string());
+        bar.enable_steady_tick(std::time::Duration::from_millis(100));
+        *ACTIVE_SPINNER.lock().unwrap_or_else(|e| e.into_inner()) = Some(bar.clone());
+        Self(Some(bar))
+    }
+
+    pub fn set_message(&self, message: &str) {
+        if let Some(bar) = &self.0 {
+            bar.set_message(message.to_string());
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        if let Some(bar) = self.0.take() {
+            bar.finish_and_clear();
+            ACTIVE_SPINNER.lock().unwrap_or_else(|e| e.into_inner()).take();
+        }
+    }
+}
+
+/// Stderr for log lines, printed above a running [`Spinner`] instead of through it
+pub struct LogWriter;
+
+impl std::io::Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let active = ACTIVE_SPINNER.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        match active {
+            Some(bar) => bar.suspend(|| std::io::stderr().write(buf)),
+            None => std::io::stderr().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::stderr().flush()
+    }
+}
+
+/// `-` stands for stdout wherever an output path is accepted
+pub fn is_stdout(path: &std::path::Path) -> bool {
+    path == std::path::Path::new("-")
+}
+
+/// Create the parent directories of `path` and truncate it, so that output can be appended to it
+pub fn create_output_file(path: &std::path::Path) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| anyhow::anyhow!("failed to create {}: {e}", parent.display()))?;
+    }
+    std::fs::File::create(path).map_err(|e| anyhow::anyhow!("failed to create {}: {e}", path.display()))?;
+    Ok(())
+}
+
+/// Render rows as a left-aligned table with a header line
+pub(crate) fn format_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    let header = header.iter().map(|h| h.to_string()).collect::<Vec<_>>();
+    for row in std::iter::once(&header).chain(rows) {
+        let line = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| format!("{cell:<width$}", width = widths[i]))
+            .collect::<Vec<_>>()
+            .join("  ");
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_color_wins_over_terminal() {
+        assert!(color_allowed(false, None, true));
+        assert!(color_allowed(false, Some("".into()), true));
+        assert!(!color_allowed(false, Some("1".into()), true));
+        assert!(!color_allowed(true, None, true));
+        assert!(!color_allowed(false, None, false));
+    }
+
+    #[test]
+    fn paints_only_with_color() {
+        assert_eq!(paint("failed", Tone::Failure, false), "failed");
+        assert_eq!(paint("failed", Tone::Failure, true), "\x1b[31mfailed\x1b[39m");
+    }
+}
@@ -0,0 +1,129 @@
+use crate::github_api::GithubClient;
+use crate::output::{format_table, OutputFormat};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Quota of one rate-limit resource, e.g. `core`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub used: u64,
+    pub remaining: u64,
+    /// Unix timestamp when the quota is replenished
+    pub reset: i64,
+}
+
+#[derive(Deserialize)]
+struct RateLimitResponse {
+    resources: BTreeMap<String, RateLimit>,
+}
+
+/// Fetch `/rate_limit` and keep the resources relevant to Actions: `core` and `actions*`
+pub async fn rate_limits(gh: &GithubClient) -> anyhow::Result<BTreeMap<String, RateLimit>> {
+    let url = format!("{}/rate_limit", gh.base_url());
+    let res: RateLimitResponse = gh.get_json(&url).await?;
+    Ok(relevant(res))
+}
+
+fn relevant(res: RateLimitResponse) -> BTreeMap<String, RateLimit> {
+    res.resources
+        .into_iter()
+        .filter(|(name, _)| name == "core" || name.starts_with("actions"))
+        .collect()
+}
+
+/// Reset time as local time, e.g. `2024-05-01 14:03:20 +02:00`
+pub fn format_reset(reset: i64) -> String {
+    match chrono::DateTime::from_timestamp(reset, 0) {
+        Some(time) => time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S %:z").to_string(),
+        None => reset.to_string(),
+    }
+}
+
+/// Remaining requests below which responses trigger a warning
+const LOW_REMAINING: u64 = 10;
+
+/// Warning text when the `X-RateLimit-*` headers of a response report a nearly exhausted quota
+pub(crate) fn low_quota_warning(headers: &HeaderMap) -> Option<String> {
+    let header = |name: &str| headers.get(name)?.to_str().ok()?.parse::<i64>().ok();
+    let remaining = header("x-ratelimit-remaining")?;
+    if remaining >= LOW_REMAINING as i64 {
+        return None;
+    }
+    let reset = header("x-ratelimit-reset")
+        .map(format_reset)
+        .unwrap_or_else(|| "unknown".to_string());
+    Some(format!("GitHub API rate limit nearly exhausted: {remaining} requests remaining, resets at {reset}"))
+}
+
+/// Render the limits as a table or JSON
+pub fn render_rate_limits(limits: &BTreeMap<String, RateLimit>, format: OutputFormat) -> anyhow::Result<String> {
+    match format {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(limits)? + "\n"),
+        OutputFormat::Text => {
+            let rows = limits
+                .iter()
+                .map(|(name, l)| {
+                    vec![
+                        name.clone(),
+                        l.limit.to_string(),
+                        l.used.to_string(),
+                        l.remaining.to_string(),
+                        format_reset(l.reset),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            Ok(format_table(&["RESOURCE", "LIMIT", "USED", "REMAINING", "RESET"], &rows))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = r#"{
+  "resources": {
+    "core": {"limit": 5000, "used": 1, "remaining": 4999, "reset": 1691591363},
+    "search": {"limit": 30, "used": 12, "remaining": 18, "reset": 1691591091},
+    "actions_runner_registration": {"limit": 10000, "used": 0, "remaining": 10000, "reset": 1691594631}
+  },
+  "rate": {"limit": 5000, "used": 1, "remaining": 4999, "reset": 1372700873}
+}"#;
+
+    #[test]
+    fn keeps_core_and_actions_limits() {
+        let limits = relevant(serde_json::from_str(BODY).unwrap());
+        assert_eq!(limits.keys().collect::<Vec<_>>(), ["actions_runner_registration", "core"]);
+
+        let table = render_rate_limits(&limits, OutputFormat::Text).unwrap();
+        let core = table.lines().find(|l| l.starts_with("core")).unwrap();
+        assert!(core.contains("5000"), "{core}");
+        assert!(core.contains("4999"), "{core}");
+        assert!(core.ends_with(&format_reset(1691591363)), "{core}");
+    }
+
+    #[test]
+    fn warns_on_low_remaining_quota() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", "3".parse().unwrap());
+        headers.insert("x-ratelimit-reset", "1691591363".parse().unwrap());
+        assert_eq!(
+            low_quota_warning(&headers).unwrap(),
+            format!("GitHub API rate limit nearly exhausted: 3 requests remaining, resets at {}", format_reset(1691591363))
+        );
+
+        headers.insert("x-ratelimit-remaining", "10".parse().unwrap());
+        assert_eq!(low_quota_warning(&headers), None);
+        assert_eq!(low_quota_warning(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn renders_json() {
+        let limits = relevant(serde_json::from_str(BODY).unwrap());
+        let json: serde_json::Value = serde_json::from_str(&render_rate_limits(&limits, OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["core"]["remaining"], 4999);
+        assert!(json.get("search").is_none());
+    }
+}
@@ -0,0 +1,633 @@
+use crate::github_api::{ApiError, GithubClient};
+use crate::output::{format_table, paint, OutputFormat, Tone};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// Workflow run as returned by the runs endpoints
+#[derive(Debug, Deserialize, Serialize)]
+pub struct WorkflowRun {
+    pub id: u64,
+    pub status: Option<String>,
+    pub conclusion: Option<String>,
+    pub event: String,
+    pub head_branch: Option<String>,
+    pub html_url: String,
+}
+
+#[derive(Deserialize)]
+struct RunsResponse {
+    workflow_runs: Vec<WorkflowRun>,
+}
+
+/// Filters for listing runs; `None` means no restriction
+#[derive(Debug, Default)]
+pub struct RunFilter {
+    pub workflow: Option<String>,
+    pub branch: Option<String>,
+    pub status: Option<String>,
+    /// Triggering event, e.g. `workflow_dispatch`
+    pub event: Option<String>,
+    /// Creation time range in GitHub search syntax, e.g. `>=2024-05-01T12:00:00Z`
+    pub created: Option<String>,
+    pub limit: Option<u32>,
+}
+
+/// `created` filter for runs created at or after `since`
+pub fn created_since(since: chrono::DateTime<chrono::Utc>) -> String {
+    format!(">={}", since.format("%Y-%m-%dT%H:%M:%SZ"))
+}
+
+/// Start of a `--since` range: a duration back from `now` like `24h` or `2weeks`,
+/// a date like `2024-01-01` (midnight UTC) or an RFC 3339 timestamp
+pub fn parse_since(value: &str, now: chrono::DateTime<chrono::Utc>) -> anyhow::Result<chrono::DateTime<chrono::Utc>> {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_time(chrono::NaiveTime::MIN).and_utc());
+    }
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(time.to_utc());
+    }
+    let duration = humantime::parse_duration(value)
+        .map_err(|e| anyhow::anyhow!("expected a duration like 24h, a date like 2024-01-01 or an RFC 3339 timestamp: {e}"))?;
+    chrono::Duration::from_std(duration)
+        .ok()
+        .and_then(|duration| now.checked_sub_signed(duration))
+        .ok_or_else(|| anyhow::anyhow!("duration {value} reaches too far back"))
+}
+
+/// Build the runs URL; a workflow filter selects the per-workflow endpoint
+pub(crate) fn runs_url(base_url: &str, repo: &str, filter: &RunFilter) -> anyhow::Result<Url> {
+    let path = match &filter.workflow {
+        Some(workflow) => format!("{base_url}/repos/{repo}/actions/workflows/{workflow}/runs"),
+        None => format!("{base_url}/repos/{repo}/actions/runs"),
+    };
+    let mut url = Url::parse(&path)?;
+    {
+        let mut query = url.query_pairs_mut();
+        if let Some(branch) = &filter.branch {
+            query.append_pair("branch", branch);
+        }
+        if let Some(status) = &filter.status {
+            query.append_pair("status", status);
+        }
+        if let Some(event) = &filter.event {
+            query.append_pair("event", event);
+        }
+        if let Some(created) = &filter.created {
+            query.append_pair("created", created);
+        }
+        if let Some(limit) = filter.limit {
+            query.append_pair("per_page", &limit.to_string());
+        }
+    }
+    if url.query() == Some("") {
+        url.set_query(None);
+    }
+    Ok(url)
+}
+
+/// Fetch runs of `repo` matching `filter`
+pub async fn list_runs(gh: &GithubClient, repo: &str, filter: &RunFilter) -> anyhow::Result<Vec<WorkflowRun>> {
+    let url = runs_url(gh.base_url(), repo, filter)?;
+    let response: RunsResponse = gh.get_json(url.as_str()).await?;
+    Ok(response.workflow_runs)
+}
+
+/// Find the run started by dispatching `workflow` on `git_ref` at `since`.
+///
+/// The dispatch endpoint does not return the run, and the run shows up with a delay,
+/// so the runs list is polled up to `attempts` times. The oldest matching run wins.
+/// `since` should lie a little before the dispatch to tolerate clock skew.
+pub async fn find_dispatched_run(
+    gh: &GithubClient,
+    repo: &str,
+    workflow: &str,
+    git_ref: &str,
+    since: chrono::DateTime<chrono::Utc>,
+    attempts: u32,
+    interval: Duration,
+) -> anyhow::Result<Option<WorkflowRun>> {
+    let branch = git_ref
+        .strip_prefix("refs/heads/")
+        .or_else(|| git_ref.strip_prefix("refs/tags/"))
+        .unwrap_or(git_ref);
+    let filter = RunFilter {
+        workflow: Some(workflow.to_string()),
+        branch: Some(branch.to_string()),
+        event: Some("workflow_dispatch".to_string()),
+        created: Some(created_since(since)),
+        ..Default::default()
+    };
+    for attempt in 1..=attempts {
+        // newest first
+        if let Some(run) = list_runs(gh, repo, &filter).await?.pop() {
+            return Ok(Some(run));
+        }
+        if attempt < attempts {
+            tokio::time::sleep(interval).await;
+        }
+    }
+    Ok(None)
+}
+
+/// Render runs as a table or as JSON
+pub fn render_runs(runs: &[WorkflowRun], format: OutputFormat) -> anyhow::Result<String> {
+    match format {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(runs)? + "\n"),
+        OutputFormat::Text => {
+            let rows = runs
+                .iter()
+                .map(|r| {
+                    vec![
+                        r.id.to_string(),
+                        r.status.clone().unwrap_or_default(),
+                        r.conclusion.clone().unwrap_or_default(),
+                        r.event.clone(),
+                        r.head_branch.clone().unwrap_or_default(),
+                        r.html_url.clone(),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            Ok(format_table(&["ID", "STATUS", "CONCLUSION", "EVENT", "BRANCH", "URL"], &rows))
+        }
+    }
+}
+
+/// Fetch a single run
+pub async fn get_run(gh: &GithubClient, repo: &str, run_id: u64) -> anyhow::Result<WorkflowRun> {
+    let url = format!("{}/repos/{repo}/actions/runs/{run_id}", gh.base_url());
+    gh.get_json(&url).await
+}
+
+/// Request cancellation of a run; GitHub answers 202 Accepted
+pub async fn cancel_run(gh: &GithubClient, repo: &str, run_id: u64) -> anyhow::Result<()> {
+    let url = format!("{}/repos/{repo}/actions/runs/{run_id}/cancel", gh.base_url());
+    gh.post_empty(&url).await?;
+    Ok(())
+}
+
+/// Re-run a whole run, or only its failed jobs; GitHub answers 201 Created
+pub async fn rerun_run(gh: &GithubClient, repo: &str, run_id: u64, failed_only: bool) -> anyhow::Result<()> {
+    let url = rerun_url(gh.base_url(), repo, run_id, failed_only);
+    gh.post_empty(&url).await?;
+    Ok(())
+}
+
+fn rerun_url(base_url: &str, repo: &str, run_id: u64, failed_only: bool) -> String {
+    let action = if failed_only { "rerun-failed-jobs" } else { "rerun" };
+    format!("{base_url}/repos/{repo}/actions/runs/{run_id}/{action}")
+}
+
+/// Download the log archive (a zip) of a run to `path`, returning its size in bytes.
+/// GitHub answers with a redirect to short-lived storage, which is followed.
+pub async fn download_logs(gh: &GithubClient, repo: &str, run_id: u64, path: &std::path::Path) -> anyhow::Result<u64> {
+    let url = format!("{}/repos/{repo}/actions/runs/{run_id}/logs", gh.base_url());
+    let res = gh.execute(gh.request(reqwest::Method::GET, &url)).await?;
+    let status = res.status();
+    if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::GONE {
+        return Err(ApiError {
+            status,
+            message: format!("logs of run {run_id} are not available; the run may still be in progress, or its logs expired or were deleted"),
+        }
+        .into());
+    }
+    if !status.is_success() {
+        let text = res.text().await?;
+        return Err(ApiError::from_body(status, &text).into());
+    }
+    let bytes = res.bytes().await?;
+    if crate::output::is_stdout(path) {
+        std::io::stdout().write_all(&bytes)?;
+    } else {
+        crate::output::create_output_file(path)?;
+        std::fs::write(path, &bytes).map_err(|e| anyhow::anyhow!("failed to write {}: {e}", path.display()))?;
+    }
+    Ok(bytes.len() as u64)
+}
+
+/// Job of a workflow run
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Job {
+    pub id: u64,
+    pub name: String,
+    pub status: Option<String>,
+    pub conclusion: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JobsResponse {
+    jobs: Vec<Job>,
+}
+
+/// Fetch the jobs of a run's latest attempt
+pub async fn list_jobs(gh: &GithubClient, repo: &str, run_id: u64) -> anyhow::Result<Vec<Job>> {
+    let url = format!("{}/repos/{repo}/actions/runs/{run_id}/jobs?per_page=100", gh.base_url());
+    let response: JobsResponse = gh.get_json(&url).await?;
+    Ok(response.jobs)
+}
+
+/// Render jobs as a table
+pub fn render_jobs(jobs: &[Job]) -> String {
+    let rows = jobs
+        .iter()
+        .map(|j| vec![j.name.clone(), j.status.clone().unwrap_or_default(), j.conclusion.clone().unwrap_or_default()])
+        .collect::<Vec<_>>();
+    format_table(&["JOB", "STATUS", "CONCLUSION"], &rows)
+}
+
+/// The watched run did not complete in time
+#[derive(Debug, thiserror::Error)]
+#[error("run did not complete within {}", format_elapsed(*.0))]
+pub struct WatchTimeout(pub Duration);
+
+/// Remembers the last seen statuses so that only transitions get reported
+#[derive(Default)]
+struct RunWatch {
+    last_status: Option<String>,
+    /// `(status, conclusion)` by job id
+    last_jobs: std::collections::HashMap<u64, (Option<String>, Option<String>)>,
+    /// Color the conclusion of a completed run
+    color: bool,
+}
+
+impl RunWatch {
+    /// Returns the line to print when `run` changed status since the last observation
+    fn observe(&mut self, run: &WorkflowRun, elapsed: Duration) -> Option<String> {
+        if run.status == self.last_status {
+            return None;
+        }
+        self.last_status = run.status.clone();
+        let status = run.status.as_deref().unwrap_or("unknown");
+        let line = match &run.conclusion {
+            Some(conclusion) if status == "completed" => {
+                format!("[{}] {status} ({})", format_elapsed(elapsed), paint(conclusion, conclusion_tone(conclusion), self.color))
+            }
+            _ => format!("[{}] {status}", format_elapsed(elapsed)),
+        };
+        Some(line)
+    }
+
+    /// Returns a line for every job that appeared or changed since the last observation
+    fn observe_jobs(&mut self, jobs: &[Job], elapsed: Duration) -> Vec<String> {
+        let mut lines = Vec::new();
+        for job in jobs {
+            let state = (job.status.clone(), job.conclusion.clone());
+            if self.last_jobs.get(&job.id) == Some(&state) {
+                continue;
+            }
+            let status = job.status.as_deref().unwrap_or("unknown");
+            let line = match &job.conclusion {
+                Some(conclusion) if status == "completed" => {
+                    format!("[{}]   {}: {status} ({})", format_elapsed(elapsed), job.name, paint(conclusion, conclusion_tone(conclusion), self.color))
+                }
+                _ => format!("[{}]   {}: {status}", format_elapsed(elapsed), job.name),
+            };
+            lines.push(line);
+            self.last_jobs.insert(job.id, state);
+        }
+        lines
+    }
+}
+
+/// Color of a run or job conclusion
+fn conclusion_tone(conclusion: &str) -> Tone {
+    match conclusion_exit_code(Some(conclusion)) {
+        exitcode::OK => Tone::Success,
+        exitcode::TEMPFAIL => Tone::Warning,
+        _ => Tone::Failure,
+    }
+}
+
+/// Poll with `fetch` every `interval` until the run completes, printing status transitions to `out`,
+/// with a colored conclusion when `color` is set. Jobs from `fetch_jobs` are reported the same way,
+/// and listed as a table once the run completed; failing to fetch them only warns, as the run decides the outcome.
+pub async fn watch_run(
+    mut fetch: impl AsyncFnMut() -> anyhow::Result<WorkflowRun>,
+    mut fetch_jobs: impl AsyncFnMut() -> anyhow::Result<Vec<Job>>,
+    interval: Duration,
+    timeout: Option<Duration>,
+    out: &mut impl Write,
+    color: bool,
+) -> anyhow::Result<WorkflowRun> {
+    let start = Instant::now();
+    let mut watch = RunWatch { color, ..Default::default() };
+    let mut jobs = Vec::new();
+    let mut jobs_failed = false;
+    loop {
+        let run = fetch().await?;
+        match fetch_jobs().await {
+            Ok(fetched) => jobs = fetched,
+            Err(e) if !jobs_failed => {
+                tracing::warn!("Listing the jobs of the run failed, watching the run only: {e}");
+                jobs_failed = true;
+            }
+            Err(_) => {}
+        }
+        if let Some(line) = watch.observe(&run, start.elapsed()) {
+            writeln!(out, "{line}")?;
+        }
+        for line in watch.observe_jobs(&jobs, start.elapsed()) {
+            writeln!(out, "{line}")?;
+        }
+        if run.status.as_deref() == Some("completed") {
+            if !jobs.is_empty() {
+                write!(out, "{}", render_jobs(&jobs))?;
+            }
+            return Ok(run);
+        }
+        if let Some(timeout) = timeout
+            && start.elapsed() >= timeout
+        {
+            return Err(WatchTimeout(timeout).into());
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Process exit code for a completed run's conclusion
+pub fn conclusion_exit_code(conclusion: Option<&str>) -> exitcode::ExitCode {
+    match conclusion {
+        Some("success") | Some("neutral") | Some("skipped") => exitcode::OK,
+        Some("cancelled") | Some("timed_out") => exitcode::TEMPFAIL,
+        Some("action_required") => exitcode::NOPERM,
+        _ => exitcode::SOFTWARE,
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 60 {
+        format!("{secs}s")
+    } else {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github_api;
+
+    const API: &str = "https://api.github.com";
+
+    fn client(server: &wiremock::MockServer) -> GithubClient {
+        GithubClient::new(&server.uri(), "t0ken", &github_api::ClientOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn runs_url_without_filters() {
+        let url = runs_url(API, "owner/repo", &RunFilter::default()).unwrap();
+        assert_eq!(url.as_str(), "https://api.github.com/repos/owner/repo/actions/runs");
+    }
+
+    #[test]
+    fn runs_url_with_all_filters() {
+        let filter = RunFilter {
+            workflow: Some("ci.yml".into()),
+            branch: Some("feature/x y".into()),
+            status: Some("completed".into()),
+            limit: Some(5),
+            ..Default::default()
+        };
+        let url = runs_url(API, "owner/repo", &filter).unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://api.github.com/repos/owner/repo/actions/workflows/ci.yml/runs?branch=feature%2Fx+y&status=completed&per_page=5"
+        );
+    }
+
+    #[test]
+    fn runs_url_with_relative_and_absolute_since() {
+        let now = chrono::DateTime::parse_from_rfc3339("2024-05-02T12:30:00Z").unwrap().to_utc();
+        let url = |since: &str| {
+            let filter = RunFilter { created: Some(created_since(parse_since(since, now).unwrap())), ..Default::default() };
+            runs_url(API, "owner/repo", &filter).unwrap().query().unwrap().to_string()
+        };
+        assert_eq!(url("24h"), "created=%3E%3D2024-05-01T12%3A30%3A00Z");
+        assert_eq!(url("1week 2days"), "created=%3E%3D2024-04-23T12%3A30%3A00Z");
+        assert_eq!(url("2024-01-01"), "created=%3E%3D2024-01-01T00%3A00%3A00Z");
+        assert_eq!(url("2024-01-01T08:00:00+02:00"), "created=%3E%3D2024-01-01T06%3A00%3A00Z");
+        assert!(parse_since("yesterday", now).unwrap_err().to_string().contains("a duration like 24h"));
+    }
+
+    #[test]
+    fn renders_runs_table() {
+        let body = r#"{"total_count":1,"workflow_runs":[{"id":42,"status":"completed","conclusion":"success","event":"push","head_branch":"main","html_url":"https://github.com/owner/repo/actions/runs/42"}]}"#;
+        let runs = serde_json::from_str::<RunsResponse>(body).unwrap().workflow_runs;
+        let table = render_runs(&runs, OutputFormat::Text).unwrap();
+        assert_eq!(table.lines().nth(1).unwrap(), "42  completed  success     push   main    https://github.com/owner/repo/actions/runs/42");
+    }
+
+    fn run(status: &str, conclusion: Option<&str>) -> WorkflowRun {
+        WorkflowRun {
+            id: 42,
+            status: Some(status.to_string()),
+            conclusion: conclusion.map(|c| c.to_string()),
+            event: "workflow_dispatch".into(),
+            head_branch: Some("main".into()),
+            html_url: "https://github.com/owner/repo/actions/runs/42".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn watch_reports_transitions_until_completed() {
+        let mut responses = std::collections::VecDeque::from(vec![
+            run("queued", None),
+            run("queued", None),
+            run("in_progress", None),
+            run("in_progress", None),
+            run("completed", Some("failure")),
+        ]);
+        let mut out = Vec::new();
+        let last = watch_run(async || Ok(responses.pop_front().unwrap()), async || Ok(Vec::new()), Duration::ZERO, None, &mut out, false)
+            .await
+            .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out, "[0s] queued\n[0s] in_progress\n[0s] completed (failure)\n");
+        assert!(responses.is_empty());
+        assert_eq!(conclusion_exit_code(last.conclusion.as_deref()), exitcode::SOFTWARE);
+    }
+
+    #[tokio::test]
+    async fn watch_reports_job_transitions_and_table() {
+        use wiremock::matchers::{method, path};
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runs/42/jobs"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(
+                r#"{"total_count":2,"jobs":[{"id":1,"name":"build","status":"completed","conclusion":"success"},{"id":2,"name":"test","status":"completed","conclusion":"failure"}]}"#,
+            ))
+            .mount(&server)
+            .await;
+        let jobs = list_jobs(&client(&server), "owner/repo", 42).await.unwrap();
+        assert_eq!(render_jobs(&jobs), "JOB    STATUS     CONCLUSION\nbuild  completed  success\ntest   completed  failure\n");
+
+        let mut job_responses = std::collections::VecDeque::from(vec![
+            vec![Job { status: Some("in_progress".into()), conclusion: None, ..jobs[0].clone() }, Job { status: Some("queued".into()), conclusion: None, ..jobs[1].clone() }],
+            jobs.clone(),
+        ]);
+        let mut runs = std::collections::VecDeque::from(vec![run("in_progress", None), run("completed", Some("failure"))]);
+        let mut out = Vec::new();
+        watch_run(async || Ok(runs.pop_front().unwrap()), async || Ok(job_responses.pop_front().unwrap()), Duration::ZERO, None, &mut out, false)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[0s] in_progress\n[0s]   build: in_progress\n[0s]   test: queued\n\
+             [0s] completed (failure)\n[0s]   build: completed (success)\n[0s]   test: completed (failure)\n\
+             JOB    STATUS     CONCLUSION\nbuild  completed  success\ntest   completed  failure\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn downloads_logs_following_the_redirect() {
+        use wiremock::matchers::{method, path};
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runs/42/logs"))
+            .respond_with(wiremock::ResponseTemplate::new(302).insert_header("Location", format!("{}/storage/logs.zip", server.uri())))
+            .mount(&server)
+            .await;
+        wiremock::Mock::given(method("GET"))
+            .and(path("/storage/logs.zip"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_bytes(b"PK\x03\x04zip".to_vec()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let target = temp.path().join("logs/run-42.zip");
+        let size = download_logs(&client(&server), "owner/repo", 42, &target).await.unwrap();
+        assert_eq!(size, 7);
+        assert_eq!(std::fs::read(&target).unwrap(), b"PK\x03\x04zip");
+    }
+
+    #[tokio::test]
+    async fn missing_logs_are_reported_clearly() {
+        use wiremock::matchers::method;
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(410).set_body_string(r#"{"message":"Gone"}"#))
+            .mount(&server)
+            .await;
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let err = download_logs(&client(&server), "owner/repo", 42, &temp.path().join("logs.zip")).await.unwrap_err();
+        assert!(err.to_string().contains("logs of run 42 are not available"));
+        assert!(!temp.path().join("logs.zip").exists());
+    }
+
+    #[tokio::test]
+    async fn watch_survives_failing_job_listings() {
+        use wiremock::matchers::{method, path};
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/runs/42/jobs"))
+            .respond_with(wiremock::ResponseTemplate::new(403).set_body_string(r#"{"message":"Resource not accessible by integration"}"#))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let gh = client(&server);
+
+        let mut runs = std::collections::VecDeque::from(vec![run("in_progress", None), run("completed", Some("success"))]);
+        let mut out = Vec::new();
+        let run = watch_run(async || Ok(runs.pop_front().unwrap()), async || list_jobs(&gh, "owner/repo", 42).await, Duration::ZERO, None, &mut out, false)
+            .await
+            .unwrap();
+
+        assert_eq!(run.conclusion.as_deref(), Some("success"));
+        assert_eq!(String::from_utf8(out).unwrap(), "[0s] in_progress\n[0s] completed (success)\n");
+    }
+
+    #[tokio::test]
+    async fn watch_times_out() {
+        let mut out = Vec::new();
+        let err = watch_run(async || Ok(run("in_progress", None)), async || Ok(Vec::new()), Duration::ZERO, Some(Duration::ZERO), &mut out, false)
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<WatchTimeout>().is_some());
+    }
+
+    #[tokio::test]
+    async fn cancel_accepts_202() {
+        use wiremock::matchers::{header, method, path};
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(method("POST"))
+            .and(path("/repos/owner/repo/actions/runs/42/cancel"))
+            .and(header("authorization", "Bearer t0ken"))
+            .respond_with(wiremock::ResponseTemplate::new(202).set_body_string("{}"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        cancel_run(&client(&server), "owner/repo", 42).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn cancel_of_completed_run_maps_to_exit_code() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("POST"))
+            .respond_with(wiremock::ResponseTemplate::new(409).set_body_string(r#"{"message":"Cannot cancel a workflow run that is completed."}"#))
+            .mount(&server)
+            .await;
+
+        let err = cancel_run(&client(&server), "owner/repo", 42).await.unwrap_err();
+        assert!(err.to_string().contains("Cannot cancel a workflow run that is completed."));
+        assert_eq!(github_api::exit_code(&err), exitcode::DATAERR);
+    }
+
+    #[tokio::test]
+    async fn finds_oldest_run_dispatched_since() {
+        use wiremock::matchers::{method, path, query_param};
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/actions/workflows/ci.yml/runs"))
+            .and(query_param("branch", "main"))
+            .and(query_param("event", "workflow_dispatch"))
+            .and(query_param("created", ">=2024-05-01T12:00:00Z"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(
+                r#"{"workflow_runs":[
+                    {"id":2,"status":"queued","conclusion":null,"event":"workflow_dispatch","head_branch":"main","html_url":"https://github.com/owner/repo/actions/runs/2"},
+                    {"id":1,"status":"queued","conclusion":null,"event":"workflow_dispatch","head_branch":"main","html_url":"https://github.com/owner/repo/actions/runs/1"}
+                ]}"#,
+            ))
+            .mount(&server)
+            .await;
+
+        let since = chrono::DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z").unwrap().to_utc();
+        let run = find_dispatched_run(&client(&server), "owner/repo", "ci.yml", "refs/heads/main", since, 1, Duration::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(run.unwrap().id, 1);
+    }
+
+    #[test]
+    fn rerun_endpoint_depends_on_failed_only() {
+        assert_eq!(rerun_url(API, "owner/repo", 7, false), "https://api.github.com/repos/owner/repo/actions/runs/7/rerun");
+        assert_eq!(rerun_url(API, "owner/repo", 7, true), "https://api.github.com/repos/owner/repo/actions/runs/7/rerun-failed-jobs");
+    }
+
+    #[tokio::test]
+    async fn rerun_failed_only_posts_to_failed_jobs_endpoint() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("POST"))
+            .and(wiremock::matchers::path("/repos/owner/repo/actions/runs/7/rerun-failed-jobs"))
+            .respond_with(wiremock::ResponseTemplate::new(201).set_body_string("{}"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        rerun_run(&client(&server), "owner/repo", 7, true).await.unwrap();
+    }
+
+    #[test]
+    fn exit_codes_from_conclusion() {
+        assert_eq!(conclusion_exit_code(Some("success")), exitcode::OK);
+        assert_eq!(conclusion_exit_code(Some("cancelled")), exitcode::TEMPFAIL);
+        assert_eq!(conclusion_exit_code(None), exitcode::SOFTWARE);
+        assert_eq!(format_elapsed(Duration::from_secs(125)), "2m05s");
+    }
+}
@@ -0,0 +1,76 @@
+#!/bin/sh
+# workflow_dispatch client generated by gha
+# Usage: $0 <target>   (inputs are taken from UPPERCASE environment variables)
+set -e
+
+if [ -f .env ]; then
+	set -a
+	. ./.env
+	set +a
+fi
+REPO="${REPO:-{{repo}}}"
+REF="${REF:-{{reference}}}"
+GITHUB_API_URL="${GITHUB_API_URL:-{{api_url}}}"
+
+# Authentication: GITHUB_TOKEN, or the token in ~/.netrc:
+# machine api.github.com login anyone password ghp_XXXXX
+github_curl() {
+	if [ -n "$GITHUB_TOKEN" ]; then
+		curl --fail -sSL -H "Authorization: Bearer $GITHUB_TOKEN" \
+			-H "X-GitHub-Api-Version: 2022-11-28" -H "Accept: application/vnd.github+json" "$@"
+	else
+		curl --fail -sSL --netrc \
+			-H "X-GitHub-Api-Version: 2022-11-28" -H "Accept: application/vnd.github+json" "$@"
+	fi
+}
+
+# Dispatch workflow file $1 with the request body read from stdin
+workflow_dispatch() {
+	github_curl "$GITHUB_API_URL/repos/$REPO/actions/workflows/$1/dispatches" -d @-
+	echo "Dispatched $1 on $REF"
+}
+
+# Fail unless variable $1 has a non-empty value $2
+require() {
+	if [ -z "$2" ]; then
+		echo "requires: $1" >&2
+		exit 1
+	fi
+}
+
+{{#each workflows}}
+{{#each targets}}
+##
+{{#each comment_lines}}
+# {{this}}
+{{/each}}
+{{function}}() {
+{{#each required_vars}}
+	require {{this}} "${{this}}"
+{{/each}}
+	jq -n --arg ref "$REF" \
+{{#each inputs}}
+{{#if fixed_value}}
+		--arg {{jq_var}} "{{fixed_value}}" \
+{{else}}
+		--arg {{jq_var}} "${{env_var}}" \
+{{/if}}
+{{/each}}
+		'{ref:$ref, inputs: ([{{#each inputs}}{{#if fixed_value}}{include:true,key:"{{name}}",value:${{jq_var}}}{{else}}{include:(${{jq_var}} != ""),key:"{{name}}",value:${{jq_var}}}{{/if}}{{#unless @last}}, {{/unless}}{{/each}}] | map(select(.include) | {(.key): .value}) | add // {})}' \
+	| workflow_dispatch '{{../file}}'
+}
+
+{{/each}}
+{{/each}}
+case "$1" in
+{{#each workflows}}
+{{#each targets}}
+	{{target}}) {{function}} ;;
+{{/each}}
+{{/each}}
+	*)
+		echo "Usage: $0 <target>" >&2
+		echo "Targets: {{#each all_targets}}{{this}} {{/each}}" >&2
+		exit 64
+		;;
+esac
@@ -0,0 +1,254 @@
+use crate::gen_client::{self, WorkflowInfo};
+use crate::output::{format_table, OutputFormat};
+use serde::de::{DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// How bad a finding is; only errors fail `gha validate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Problem found in a workflow file
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Finding {
+    pub file: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Finding {
+    fn error(file: &str, message: String) -> Self {
+        Self { file: file.to_string(), severity: Severity::Error, message }
+    }
+
+    fn warning(file: &str, message: String) -> Self {
+        Self { file: file.to_string(), severity: Severity::Warning, message }
+    }
+}
+
+/// Check every workflow file in `dir`; files without `workflow_dispatch` only get the YAML checks
+pub fn validate_dir(dir: &Path) -> anyhow::Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+    for path in gen_client::workflow_files(dir)? {
+        let file = path.file_name().unwrap().to_string_lossy().to_string();
+        let yaml = match std::fs::read_to_string(&path) {
+            Ok(yaml) => yaml,
+            Err(e) => {
+                findings.push(Finding::error(&file, format!("unreadable: {e}")));
+                continue;
+            }
+        };
+        if let Some(twin) = gen_client::yml_twin(&path) {
+            let twin = twin.file_name().unwrap().to_string_lossy().to_string();
+            findings.push(Finding::warning(&file, format!("ignored when generating clients and detecting the workflow, {twin} has the same name")));
+        }
+        findings.extend(validate_yaml(&file, &yaml));
+    }
+    Ok(findings)
+}
+
+/// Check the workflow `file` with content `yaml`
+pub fn validate_yaml(file: &str, yaml: &str) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = duplicate_keys(yaml)
+        .into_iter()
+        .map(|key| Finding::error(file, format!("duplicate key {key}; only the last one takes effect")))
+        .collect();
+    match gen_client::parse_workflow_yaml(file, yaml) {
+        Ok(Some(info)) => findings.extend(validate_workflow(&info)),
+        Ok(None) => {}
+        Err(e) => findings.push(Finding::error(file, format!("invalid YAML: {e}"))),
+    }
+    findings
+}
+
+/// Check the dispatch inputs of a parsed workflow
+pub fn validate_workflow(info: &WorkflowInfo) -> Vec<Finding> {
+    let file = info.file.as_str();
+    let mut findings = Vec::new();
+    if info.inputs.len() > crate::dispatch::MAX_INPUTS {
+        findings.push(Finding::error(
+            file,
+            format!("{} inputs, but GitHub accepts at most {}", info.inputs.len(), crate::dispatch::MAX_INPUTS),
+        ));
+    }
+    for input in &info.inputs {
+        let name = &input.name;
+        if input.ui_type.as_deref() == Some("choice") {
+            if input.options.is_empty() {
+                findings.push(Finding::error(file, format!("choice input '{name}' has no options")));
+            } else if let Some(default) = &input.default
+                && !input.options.contains(default)
+            {
+                findings.push(Finding::error(file, format!("default '{default}' of choice input '{name}' is not one of its options")));
+            }
+        }
+        if input.is_required(gen_client::RequiredSemantics::NoDefault) && input.description.is_none() {
+            findings.push(Finding::warning(
+                file,
+                format!("required input '{name}' has neither a default nor a description"),
+            ));
+        }
+    }
+    findings
+}
+
+/// Findings as a table, or as JSON
+pub fn render_findings(findings: &[Finding], format: OutputFormat) -> anyhow::Result<String> {
+    if format == OutputFormat::Json {
+        return Ok(serde_json::to_string_pretty(findings)? + "\n");
+    }
+    if findings.is_empty() {
+        return Ok("No problems found\n".to_string());
+    }
+    let rows = findings
+        .iter()
+        .map(|f| {
+            let severity = match f.severity {
+                Severity::Warning => "warning",
+                Severity::Error => "error",
+            };
+            vec![f.file.clone(), severity.to_string(), f.message.clone()]
+        })
+        .collect::<Vec<_>>();
+    Ok(format_table(&["FILE", "SEVERITY", "PROBLEM"], &rows))
+}
+
+/// Dotted paths of mapping keys that occur more than once, e.g. `on.workflow_dispatch.inputs.tag`.
+/// Parsing into a value keeps only the last of them, so this walks the document itself.
+fn duplicate_keys(yaml: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let deserializer = serde_yml::Deserializer::from_str(yaml);
+    // invalid YAML is reported by the parser
+    let _ = DuplicateKeys { path: String::new(), found: &mut found }.deserialize(deserializer);
+    found
+}
+
+/// Visitor recording the duplicate keys below `path`
+struct DuplicateKeys<'a> {
+    path: String,
+    found: &'a mut Vec<String>,
+}
+
+impl DuplicateKeys<'_> {
+    fn child(&self, key: &str) -> String {
+        if self.path.is_empty() { key.to_string() } else { format!("{}.{key}", self.path) }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for DuplicateKeys<'_> {
+    type Value = ();
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for DuplicateKeys<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("any YAML value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_none<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_some<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut index = 0;
+        loop {
+            let path = format!("{}[{index}]", self.path);
+            if seq.next_element_seed(DuplicateKeys { path, found: &mut *self.found })?.is_none() {
+                return Ok(());
+            }
+            index += 1;
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut seen = HashSet::new();
+        while let Some(key) = map.next_key::<serde_yml::Value>()? {
+            let key = match key {
+                serde_yml::Value::String(key) => key,
+                other => serde_yml::to_string(&other).unwrap_or_default().trim().to_string(),
+            };
+            let path = self.child(&key);
+            if !seen.insert(key) {
+                self.found.push(path.clone());
+            }
+            map.next_value_seed(DuplicateKeys { path, found: &mut *self.found })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_each_diagnostic_of_the_fixtures() {
+        let findings = validate_dir(Path::new("tests/validate")).unwrap();
+        let summary: Vec<(&str, Severity, &str)> = findings
+            .iter()
+            .map(|f| (f.file.as_str(), f.severity, f.message.as_str()))
+            .collect();
+
+        assert_eq!(summary.len(), 6, "{summary:#?}");
+        assert_eq!(summary[0].0, "broken.yml");
+        assert!(summary[0].2.starts_with("invalid YAML"));
+        assert_eq!(
+            summary[1..],
+            [
+                ("duplicate.yml", Severity::Error, "duplicate key on.workflow_dispatch.inputs.tag; only the last one takes effect"),
+                ("empty_choice.yml", Severity::Error, "choice input 'target' has no options"),
+                ("empty_choice.yml", Severity::Error, "default 'trace' of choice input 'level' is not one of its options"),
+                ("too_many.yml", Severity::Error, "11 inputs, but GitHub accepts at most 10"),
+                ("undocumented.yml", Severity::Warning, "required input 'token' has neither a default nor a description"),
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_findings_table() {
+        let findings = vec![Finding::warning("ci.yml", "required input 'token' has neither a default nor a description".into())];
+        assert_eq!(
+            render_findings(&findings, OutputFormat::Text).unwrap(),
+            "FILE    SEVERITY  PROBLEM\nci.yml  warning   required input 'token' has neither a default nor a description\n"
+        );
+        assert_eq!(render_findings(&[], OutputFormat::Text).unwrap(), "No problems found\n");
+    }
+}
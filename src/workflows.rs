@@ -0,0 +1,236 @@
+use crate::gen_client::{self, WorkflowInfo};
+use crate::github_api::GithubClient;
+use crate::output::{format_table, OutputFormat};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Workflow as returned by `/repos/{repo}/actions/workflows`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Workflow {
+    pub id: u64,
+    pub name: String,
+    pub path: String,
+    pub state: String,
+}
+
+#[derive(Deserialize)]
+struct WorkflowsResponse {
+    workflows: Vec<Workflow>,
+}
+
+/// Fetch the workflows defined in `repo`
+pub async fn list_workflows(gh: &GithubClient, repo: &str) -> anyhow::Result<Vec<Workflow>> {
+    let url = format!("{}/repos/{repo}/actions/workflows?per_page=100", gh.base_url());
+    let response: WorkflowsResponse = gh.get_json(&url).await?;
+    Ok(response.workflows)
+}
+
+/// Fetch `workflow`, a file name like `ci.yml` or a workflow id, of `repo`
+pub async fn get_workflow(gh: &GithubClient, repo: &str, workflow: &str) -> anyhow::Result<Workflow> {
+    let url = format!("{}/repos/{repo}/actions/workflows/{workflow}", gh.base_url());
+    gh.get_json(&url).await
+}
+
+/// File name or id of `workflow` as the workflow endpoints accept it.
+/// Anything that is neither (e.g. a display name like "Release builds") is looked up in the list of workflows.
+pub async fn workflow_id(gh: &GithubClient, repo: &str, workflow: &str) -> anyhow::Result<String> {
+    if workflow.ends_with(".yml") || workflow.ends_with(".yaml") || workflow.parse::<u64>().is_ok() {
+        return Ok(workflow.to_string());
+    }
+    let found = list_workflows(gh, repo).await?.into_iter().find(|w| w.name == workflow);
+    match found {
+        Some(w) => Ok(w.id.to_string()),
+        None => anyhow::bail!("no workflow named {workflow:?} in {repo}"),
+    }
+}
+
+/// Enable or disable `workflow`; GitHub answers 204 No Content
+pub async fn set_workflow_enabled(gh: &GithubClient, repo: &str, workflow: &str, enabled: bool) -> anyhow::Result<()> {
+    let action = if enabled { "enable" } else { "disable" };
+    let url = format!("{}/repos/{repo}/actions/workflows/{workflow}/{action}", gh.base_url());
+    gh.put_empty(&url).await?;
+    Ok(())
+}
+
+/// Render workflows as a table or as JSON
+pub fn render_workflows(workflows: &[Workflow], format: OutputFormat) -> anyhow::Result<String> {
+    match format {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(workflows)? + "\n"),
+        OutputFormat::Text => {
+            let rows = workflows
+                .iter()
+                .map(|w| vec![w.name.clone(), w.path.clone(), w.id.to_string(), w.state.clone()])
+                .collect::<Vec<_>>();
+            Ok(format_table(&["NAME", "PATH", "ID", "STATE"], &rows))
+        }
+    }
+}
+
+/// Read the dispatch inputs of `workflow` from `.github/workflows` under `base_dir`
+pub fn local_workflow(base_dir: &Path, workflow: &str) -> anyhow::Result<WorkflowInfo> {
+    let path = base_dir.join(".github/workflows").join(workflow);
+    gen_client::parse_workflow(&path)?
+        .with_context(|| format!("{} has no workflow_dispatch trigger", path.display()))
+}
+
+/// All workflows with a `workflow_dispatch` trigger in `.github/workflows` under `base_dir`, by file name
+pub fn local_dispatchable_workflows(base_dir: &Path) -> anyhow::Result<Vec<WorkflowInfo>> {
+    let dir = base_dir.join(".github/workflows");
+    let mut infos = Vec::new();
+    for path in gen_client::distinct_workflow_files(&dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        infos.extend(gen_client::parse_workflow(&path)?);
+    }
+    Ok(infos)
+}
+
+/// Fetch `workflow` through the contents API and read its dispatch inputs
+pub async fn remote_workflow(gh: &GithubClient, repo: &str, workflow: &str, r#ref: Option<&str>) -> anyhow::Result<WorkflowInfo> {
+    let mut url = format!("{}/repos/{repo}/contents/.github/workflows/{workflow}", gh.base_url());
+    if let Some(r#ref) = r#ref {
+        url.push_str(&format!("?ref={ref}"));
+    }
+    let yaml = gh.get_raw(&url).await?;
+    gen_client::parse_workflow_yaml(workflow, &yaml)
+        .with_context(|| format!("failed to parse {workflow} from {repo}"))?
+        .with_context(|| format!("{workflow} has no workflow_dispatch trigger"))
+}
+
+/// Render the dispatch inputs as a table, listing choice options inline
+pub fn render_inputs(info: &WorkflowInfo, format: OutputFormat) -> anyhow::Result<String> {
+    if format == OutputFormat::Json {
+        return Ok(serde_json::to_string_pretty(info)? + "\n");
+    }
+    let mut out = format!("{} ({})\n", info.name, info.file);
+    if info.inputs.is_empty() {
+        out.push_str("No inputs\n");
+        return Ok(out);
+    }
+    let rows = info
+        .inputs
+        .iter()
+        .map(|i| {
+            let mut description = i.description.clone().unwrap_or_default();
+            if !i.options.is_empty() {
+                description = format!("{description} [options: {}]", i.options.join(", ")).trim_start().to_string();
+            }
+            vec![
+                i.name.clone(),
+                i.ui_type.clone().unwrap_or_else(|| "string".to_string()),
+                if i.required { "yes" } else { "no" }.to_string(),
+                i.default.clone().unwrap_or_default(),
+                description,
+            ]
+        })
+        .collect::<Vec<_>>();
+    out.push_str(&format_table(&["NAME", "TYPE", "REQUIRED", "DEFAULT", "DESCRIPTION"], &rows));
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPONSE: &str = r#"{
+      "total_count": 2,
+      "workflows": [
+        {"id": 161335, "node_id": "MDg6V29ya2Zsb3cxNjEzMzU=", "name": "CI", "path": ".github/workflows/ci.yml", "state": "active"},
+        {"id": 269289, "node_id": "MDE4OldvcmtmbG93IFJ1bjI2OTI4OQ==", "name": "Release builds", "path": ".github/workflows/release.yml", "state": "disabled_manually"}
+      ]
+    }"#;
+
+    fn parse() -> Vec<Workflow> {
+        serde_json::from_str::<WorkflowsResponse>(RESPONSE).unwrap().workflows
+    }
+
+    #[test]
+    fn renders_workflow_table() {
+        let table = render_workflows(&parse(), OutputFormat::Text).unwrap();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "NAME            PATH                           ID      STATE");
+        assert_eq!(lines[1], "CI              .github/workflows/ci.yml       161335  active");
+        assert_eq!(lines[2], "Release builds  .github/workflows/release.yml  269289  disabled_manually");
+    }
+
+    #[test]
+    fn renders_workflow_json() {
+        let json = render_workflows(&parse(), OutputFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[1]["path"], ".github/workflows/release.yml");
+        assert_eq!(value[1]["id"], 269289);
+        assert_eq!(value[1]["state"], "disabled_manually");
+        assert!(value[0].get("node_id").is_none());
+    }
+
+    fn client(server: &wiremock::MockServer) -> GithubClient {
+        GithubClient::new(&server.uri(), "t0ken", &crate::github_api::ClientOptions::default()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn enable_and_disable_accept_204() {
+        use wiremock::matchers::{method, path};
+        let server = wiremock::MockServer::start().await;
+        for action in ["enable", "disable"] {
+            wiremock::Mock::given(method("PUT"))
+                .and(path(format!("/repos/owner/repo/actions/workflows/ci.yml/{action}")))
+                .respond_with(wiremock::ResponseTemplate::new(204))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        set_workflow_enabled(&client(&server), "owner/repo", "ci.yml", true).await.unwrap();
+        set_workflow_enabled(&client(&server), "owner/repo", "ci.yml", false).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn enable_of_unknown_workflow_maps_to_exit_code() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("PUT"))
+            .respond_with(wiremock::ResponseTemplate::new(404).set_body_string(r#"{"message":"Not Found"}"#))
+            .mount(&server)
+            .await;
+
+        let err = set_workflow_enabled(&client(&server), "owner/repo", "gone.yml", true).await.unwrap_err();
+        assert_eq!(crate::github_api::exit_code(&err), exitcode::UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn workflow_id_looks_up_display_names() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::path("/repos/owner/repo/actions/workflows"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(RESPONSE))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let gh = client(&server);
+        assert_eq!(workflow_id(&gh, "owner/repo", "ci.yml").await.unwrap(), "ci.yml");
+        assert_eq!(workflow_id(&gh, "owner/repo", "161335").await.unwrap(), "161335");
+        assert_eq!(workflow_id(&gh, "owner/repo", "Release builds").await.unwrap(), "269289");
+        let err = workflow_id(&gh, "owner/repo", "Nightly").await.unwrap_err();
+        assert!(err.to_string().contains("no workflow named \"Nightly\""));
+    }
+
+    #[test]
+    fn renders_inputs_of_local_workflow() {
+        let info = gen_client::parse_workflow(Path::new("tests/dispatch_inputs.yml")).unwrap().unwrap();
+        let text = render_inputs(&info, OutputFormat::Text).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Deploy (dispatch_inputs.yml)");
+        assert!(lines[1].starts_with("NAME"));
+        assert!(text.contains("dry_run      boolean  no        false"));
+        assert!(text.contains("environment  choice   no        staging  Target environment [options: staging, production]"));
+        assert!(text.contains("version      string   yes                Version to deploy"));
+    }
+
+    #[test]
+    fn local_workflow_without_dispatch_is_an_error() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join(".github/workflows")).unwrap();
+        std::fs::write(temp.path().join(".github/workflows/push.yml"), "on: push\njobs: {}\n").unwrap();
+
+        let err = local_workflow(temp.path(), "push.yml").unwrap_err();
+        assert!(err.to_string().contains("has no workflow_dispatch trigger"));
+    }
+}
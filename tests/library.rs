@@ -0,0 +1,309 @@
+use assert_fs::TempDir;
+use gha::dispatch::DispatchOptions;
+use gha::{DispatchError, DispatchOutcome};
+use gha::github_api::{ClientOptions, GithubClient};
+use std::fs;
+use std::time::Duration;
+use wiremock::matchers::{body_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn client(base_url: &str) -> GithubClient {
+    GithubClient::new(base_url, "t0ken", &ClientOptions::default()).unwrap()
+}
+
+#[test]
+fn parse_args_reads_inline_and_file_values() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("notes.txt");
+    fs::write(&file, "from file").unwrap();
+
+    let inputs = gha::parse_args(&[
+        "name=World".to_string(),
+        format!("notes=@{}", file.display()),
+    ])
+    .unwrap();
+
+    assert_eq!(inputs["name"], "World");
+    assert_eq!(inputs["notes"], "from file");
+    assert!(gha::parse_args(&["no-equals-sign".to_string()]).is_err());
+}
+
+#[tokio::test]
+async fn workflow_dispatch_rejects_unknown_mode() {
+    let err = gha::workflow_dispatch(&client("https://api.github.com"), "owner/repo", "ci.yml", "main", &[], &DispatchOptions::new("bogus"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Invalid mode: bogus");
+    assert!(matches!(err, DispatchError::InvalidMode(_)));
+    assert_eq!(err.exit_code(), exitcode::USAGE);
+}
+
+#[tokio::test]
+async fn workflow_dispatch_reports_what_it_did() {
+    let gh = client("https://api.github.com");
+    let outcome = gha::workflow_dispatch(&gh, "owner/repo", "ci.yml", "main", &[], &DispatchOptions::new("curl")).await.unwrap();
+    assert_eq!(outcome, DispatchOutcome::Printed);
+
+    let options = DispatchOptions { dry_run: true, ..DispatchOptions::new("call") };
+    let outcome = gha::workflow_dispatch(&gh, "owner/repo", "ci.yml", "main", &[], &options).await.unwrap();
+    assert_eq!(outcome, DispatchOutcome::DryRun);
+
+    let err = gha::workflow_dispatch(&gh, "owner/repo", "ci.yml", "main", &["no-equals-sign".to_string()], &options)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DispatchError::InvalidArg(_)), "{err:?}");
+}
+
+#[tokio::test]
+async fn workflow_dispatch_returns_the_reported_run() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_json(serde_json::json!({"ref": "main", "inputs": {}})))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"{"workflow_run_id":7,"html_url":"https://github.com/owner/repo/actions/runs/7"}"#,
+        ))
+        .mount(&server)
+        .await;
+
+    let gh = client(&server.uri());
+    let options = DispatchOptions::new("call");
+    let outcome = gha::workflow_dispatch(&gh, "owner/repo", "ci.yml", "main", &[], &options).await.unwrap();
+    assert_eq!(outcome, DispatchOutcome::Dispatched { run_url: None });
+    let outcome = gha::workflow_dispatch(&gh, "owner/repo", "ci.yml", "v1", &[], &options).await.unwrap();
+    assert_eq!(outcome, DispatchOutcome::Dispatched { run_url: Some("https://github.com/owner/repo/actions/runs/7".into()) });
+}
+
+#[test]
+fn generate_makefile_writes_targets() {
+    let temp = TempDir::new().unwrap();
+    fs::copy("tests/dispatch_inputs.yml", temp.path().join("deploy.yml")).unwrap();
+    let output = temp.path().join("out.mk");
+
+    gha::generate_makefile(temp.path(), &output).unwrap();
+
+    let rendered = fs::read_to_string(output).unwrap();
+    assert!(rendered.contains("deploy: async-deploy await"));
+}
+
+#[tokio::test]
+async fn workflow_dispatch_call_posts_payload_to_api() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/repos/owner/repo/actions/workflows/ci.yml/dispatches"))
+        .and(header("authorization", "Bearer t0ken"))
+        .and(header("accept", "application/vnd.github+json"))
+        .and(header("x-github-api-version", "2022-11-28"))
+        .and(body_json(serde_json::json!({"ref": "main", "inputs": {"name": "World"}})))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    gha::workflow_dispatch(&client(&server.uri()), "owner/repo", "ci.yml", "main", &["name=World".to_string()], &DispatchOptions::new("call"))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn workflow_dispatch_call_reports_api_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(422).set_body_string(r#"{"message":"Unexpected inputs provided: [\"bogus\"]"}"#))
+        .mount(&server)
+        .await;
+
+    let err = gha::workflow_dispatch(&client(&server.uri()), "owner/repo", "ci.yml", "main", &["bogus=1".to_string()], &DispatchOptions::new("call"))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("bogus: unexpected input"));
+    assert!(matches!(err, DispatchError::Api(_)));
+    assert_eq!(err.exit_code(), exitcode::DATAERR);
+}
+
+#[tokio::test]
+async fn workflow_dispatch_call_honors_timeout() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(204).set_delay(Duration::from_secs(5)))
+        .mount(&server)
+        .await;
+
+    let options = ClientOptions { timeout: Some(Duration::from_millis(200)), ..Default::default() };
+    let gh = GithubClient::new(&server.uri(), "t0ken", &options).unwrap();
+    let err = gha::workflow_dispatch(&gh, "owner/repo", "ci.yml", "main", &[], &DispatchOptions::new("call"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.exit_code(), exitcode::TEMPFAIL);
+    let DispatchError::Request(err) = err else { panic!("expected an HTTP error, got {err:?}") };
+    assert!(err.is_timeout());
+}
+
+#[tokio::test]
+async fn workflow_dispatch_dry_run_sends_nothing() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let options = DispatchOptions { dry_run: true, ..DispatchOptions::new("call") };
+    gha::workflow_dispatch(&client(&server.uri()), "owner/repo", "ci.yml", "main", &["name=World".to_string()], &options)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn workflow_dispatch_call_sends_custom_headers() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(header("x-corp-auth", "secret"))
+        .and(header("accept", "application/vnd.github.v3+json"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let headers = vec![
+        gha::dispatch::parse_header("X-Corp-Auth: secret").unwrap(),
+        gha::dispatch::parse_header("Accept: application/vnd.github.v3+json").unwrap(),
+    ];
+    let options = DispatchOptions { headers, ..DispatchOptions::new("call") };
+    gha::workflow_dispatch(&client(&server.uri()), "owner/repo", "ci.yml", "main", &[], &options)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn workflow_dispatch_call_sends_api_version_override() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(header("x-github-api-version", "2026-03-10"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let options = ClientOptions { api_version: Some("2026-03-10".into()), ..Default::default() };
+    let gh = GithubClient::new(&server.uri(), "t0ken", &options).unwrap();
+    gha::workflow_dispatch(&gh, "owner/repo", "ci.yml", "main", &[], &DispatchOptions::new("call"))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn workflow_dispatch_verify_ref_accepts_tag() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/git/ref/heads/v1.0"))
+        .respond_with(ResponseTemplate::new(404).set_body_string(r#"{"message":"Not Found"}"#))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/git/ref/tags/v1.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"ref":"refs/tags/v1.0"}"#))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let options = DispatchOptions { verify_ref: true, ..DispatchOptions::new("call") };
+    gha::workflow_dispatch(&client(&server.uri()), "owner/repo", "ci.yml", "v1.0", &[], &options)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn workflow_dispatch_fail_on_disabled_stops_before_posting() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/actions/workflows/ci.yml"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"{"id": 161335, "name": "CI", "path": ".github/workflows/ci.yml", "state": "disabled_manually"}"#,
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let options = DispatchOptions { fail_on_disabled: true, ..DispatchOptions::new("call") };
+    let err = gha::workflow_dispatch(&client(&server.uri()), "owner/repo", "ci.yml", "main", &[], &options)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DispatchError::WorkflowDisabled { .. }));
+    assert!(err.to_string().starts_with("workflow ci.yml is disabled_manually; enable it first"));
+    assert_eq!(err.exit_code(), exitcode::UNAVAILABLE);
+
+    // the printing modes send nothing
+    let options = DispatchOptions { fail_on_disabled: true, ..DispatchOptions::new("curl") };
+    gha::workflow_dispatch(&client(&server.uri()), "owner/repo", "ci.yml", "main", &[], &options)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn workflow_dispatch_verify_ref_fails_before_posting() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404).set_body_string(r#"{"message":"Not Found"}"#))
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let options = DispatchOptions { verify_ref: true, ..DispatchOptions::new("call") };
+    let err = gha::workflow_dispatch(&client(&server.uri()), "owner/repo", "ci.yml", "nope", &[], &options)
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "ref 'nope' not found in owner/repo: no such branch or tag");
+    assert!(matches!(err, DispatchError::RefNotFound { .. }));
+    assert_eq!(err.exit_code(), exitcode::UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn workflow_dispatch_resolves_pull_request_ref() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/owner/repo/pulls/123"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"number":123,"head":{"ref":"feature/login","sha":"abc"}}"#))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_json(serde_json::json!({"ref": "feature/login", "inputs": {}})))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    gha::workflow_dispatch(&client(&server.uri()), "owner/repo", "ci.yml", "pr/123", &[], &DispatchOptions::new("call"))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn resolve_pr_ref_keeps_ref_when_lookup_is_optional() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404).set_body_string(r#"{"message":"Not Found"}"#))
+        .mount(&server)
+        .await;
+
+    let gh = client(&server.uri());
+    assert_eq!(gha::dispatch::resolve_pr_ref(&gh, "owner/repo", "#9", false).await.unwrap(), "#9");
+    assert!(gha::dispatch::resolve_pr_ref(&gh, "owner/repo", "#9", true).await.is_err());
+}